@@ -0,0 +1,106 @@
+#ifndef PRIME_SIEVE_H
+#define PRIME_SIEVE_H
+
+/* Generated by cbindgen from src/ffi.rs - do not edit by hand. */
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/**
+ * The result of a call into the C API.
+ */
+typedef enum SieveStatus {
+  /**
+   * The call succeeded and its output has been written.
+   */
+  SIEVE_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  SIEVE_STATUS_NULL_POINTER = 1,
+  /**
+   * The sieve hasn't been populated.
+   */
+  SIEVE_STATUS_NOT_FILLED = 2,
+  /**
+   * The value asked about is outside the numbers the sieve covers.
+   */
+  SIEVE_STATUS_OUT_OF_BOUNDS = 3,
+  /**
+   * There's no prime that satisfies the query within the sieve.
+   */
+  SIEVE_STATUS_NOT_FOUND = 4,
+  /**
+   * Rust panicked; nothing has been written.
+   */
+  SIEVE_STATUS_PANIC = 5,
+  /**
+   * An argument was outside the domain of the query (e.g. `0`).
+   */
+  SIEVE_STATUS_INVALID_ARGUMENT = 6,
+  /**
+   * The answer is too large to fit in the output type.
+   */
+  SIEVE_STATUS_OVERFLOW = 7,
+} SieveStatus;
+
+typedef struct Sieve Sieve;
+
+/**
+ * Create and populate a prime sieve with the maximum value `max`.
+ *
+ * Returns null if the sieve couldn't be created. The sieve must be released with `sieve_free`.
+ */
+struct Sieve *sieve_new(uint64_t max);
+
+/**
+ * Release a sieve created by `sieve_new`. Does nothing if `sieve` is null.
+ *
+ * # Safety
+ *
+ * `sieve` must be null or a pointer returned by `sieve_new` that hasn't already been freed.
+ */
+void sieve_free(struct Sieve *sieve);
+
+/**
+ * Write the max value of `sieve` to `out`.
+ *
+ * # Safety
+ *
+ * `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+ * writes.
+ */
+enum SieveStatus sieve_max(const struct Sieve *sieve, uint64_t *out);
+
+/**
+ * Write whether `target` is prime to `out`.
+ *
+ * # Safety
+ *
+ * `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+ * writes.
+ */
+enum SieveStatus sieve_lookup(const struct Sieve *sieve, uint64_t target, bool *out);
+
+/**
+ * Write the number of primes in `sieve` to `out`.
+ *
+ * # Safety
+ *
+ * `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+ * writes.
+ */
+enum SieveStatus sieve_count_primes(const struct Sieve *sieve, uint64_t *out);
+
+/**
+ * Write the smallest prime strictly greater than `n` to `out`, or return `SIEVE_STATUS_NOT_FOUND`
+ * if there isn't one within the sieve.
+ *
+ * # Safety
+ *
+ * `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+ * writes.
+ */
+enum SieveStatus sieve_next_prime(const struct Sieve *sieve, uint64_t n, uint64_t *out);
+
+#endif  /* PRIME_SIEVE_H */
@@ -0,0 +1,70 @@
+//! The Sieve of Atkin, as an alternative way of populating a full sieve table.
+
+use crate::bits::BitTable;
+use crate::{table_len, wheel};
+
+/// Build the full (wheel-30) table for `0..=max` using the Sieve of Atkin.
+pub(crate) fn sieve(max: u64) -> BitTable {
+    let mut table = BitTable::zeros(table_len(max));
+    let mut toggle = |n: u64| {
+        // Multiples of 5 aren't on the wheel, and 5 itself is handled separately
+        if !n.is_multiple_of(5) {
+            table.toggle(wheel::index(n) as usize);
+        }
+    };
+
+    // The smallest number any `x` can contribute is `3x² - (x - 1)²`
+    let mut x = 1;
+    while 2 * x * x + 2 * x - 1 <= max {
+        let mut y = 1;
+        loop {
+            let n = 4 * x * x + y * y;
+            if n > max {
+                break;
+            }
+            if n % 12 == 1 || n % 12 == 5 {
+                toggle(n);
+            }
+            y += 1;
+        }
+
+        let mut y = 1;
+        loop {
+            let n = 3 * x * x + y * y;
+            if n > max {
+                break;
+            }
+            if n % 12 == 7 {
+                toggle(n);
+            }
+            y += 1;
+        }
+
+        for y in (1..x).rev() {
+            let n = 3 * x * x - y * y;
+            if n > max {
+                break;
+            }
+            if n % 12 == 11 {
+                toggle(n);
+            }
+        }
+        x += 1;
+    }
+
+    // Everything left with an odd number of solutions is either prime or has a square factor
+    for n in (1..).map(wheel::value).take_while(|&n| n * n <= max) {
+        if !table.get(wheel::index(n) as usize) {
+            continue;
+        }
+        let square = n * n;
+        for multiple in (0..).map(|i| square * wheel::value(i)) {
+            if multiple > max {
+                break;
+            }
+            table.clear(wheel::index(multiple) as usize);
+        }
+    }
+
+    table
+}
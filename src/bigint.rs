@@ -0,0 +1,175 @@
+//! Primality testing for arbitrary-precision integers, using `num-bigint`.
+
+#[cfg(feature = "rand")]
+use alloc::vec;
+use core::convert::TryFrom;
+
+use num_bigint::BigUint;
+
+use crate::primality::miller_rabin;
+use crate::Sieve;
+
+impl Sieve {
+    /// Determine whether `n` is probably prime, by trial division against every prime in this sieve
+    /// followed by a strong pseudoprime (Miller-Rabin) test to each of the first `rounds` primes
+    /// as bases.
+    ///
+    /// The bases are fixed, so repeated calls give the same answer, and there's no per-round
+    /// probability of a composite getting through: composites that fool any given set of bases
+    /// exist, though they're vanishingly rare among typical candidates. Use
+    /// `Sieve::is_probable_prime_with_rng()` (with the `rand` feature) for random bases, which
+    /// bound that probability however `n` was chosen. Values that fit in a `u64` are answered
+    /// exactly regardless of `rounds`.
+    ///
+    /// Trial division screens out most composites quickly, but costs a division for every prime
+    /// in the sieve, so a sieve with a max of a few thousand to a million is a good fit. With an
+    /// unfilled sieve this does no trial division.
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    ///
+    /// let my_sieve = prime_sieve::Sieve::new(10_000);
+    ///
+    /// let mersenne = (BigUint::from(1u32) << 127u32) - 1u32;
+    /// assert!(my_sieve.is_probable_prime(&mersenne, 20));
+    /// assert!(!my_sieve.is_probable_prime(&(mersenne + 2u32), 20));
+    /// ```
+    pub fn is_probable_prime(&self, n: &BigUint, rounds: usize) -> bool {
+        self.test_big(
+            n,
+            (2..)
+                .filter(|&base| miller_rabin(base))
+                .take(rounds)
+                .map(BigUint::from),
+        )
+    }
+
+    /// Determine whether `n` is probably prime like `Sieve::is_probable_prime()`, but with
+    /// `rounds` bases drawn uniformly at random from `2..=n - 2` using `rng`.
+    ///
+    /// Each round lets a composite through with probability at most 1/4, so one is reported
+    /// prime with probability at most `4^-rounds` whatever it is.
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    /// use rand::SeedableRng;
+    ///
+    /// let my_sieve = prime_sieve::Sieve::new(10_000);
+    /// let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+    ///
+    /// let mersenne = (BigUint::from(1u32) << 127u32) - 1u32;
+    /// assert!(my_sieve.is_probable_prime_with_rng(&mersenne, 20, &mut rng));
+    /// assert!(!my_sieve.is_probable_prime_with_rng(&(mersenne + 2u32), 20, &mut rng));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn is_probable_prime_with_rng<G: rand::Rng + ?Sized>(
+        &self,
+        n: &BigUint,
+        rounds: usize,
+        rng: &mut G,
+    ) -> bool {
+        let bases = (0..rounds).map(|_| random_base(n, rng));
+        self.test_big(n, bases)
+    }
+
+    // Trial divide `n`, then test it to each of `bases` (which must be in `2..=n - 2`), answering
+    // exactly if it fits in a `u64`
+    fn test_big<I: Iterator<Item = BigUint>>(&self, n: &BigUint, bases: I) -> bool {
+        if let Ok(small) = u64::try_from(n) {
+            return self.lookup_or_test(small);
+        }
+        // Everything from here on is bigger than any of its trial divisors
+        if !n.bit(0) || self.primes().any(|p| n % p == BigUint::ZERO) {
+            return false;
+        }
+
+        let one = BigUint::from(1u32);
+        let n_minus_one = n - &one;
+        let shift = n_minus_one
+            .trailing_zeros()
+            .expect("n - 1 isn't zero, since n is big");
+        let odd_part = &n_minus_one >> shift;
+        let mut bases = bases;
+        bases.all(|base| {
+            let mut x = base.modpow(&odd_part, n);
+            if x == one || x == n_minus_one {
+                return true;
+            }
+            for _ in 1..shift {
+                x = &x * &x % n;
+                if x == n_minus_one {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+}
+
+// A uniformly random base in `2..=n - 2`, for `n` bigger than a `u64`. Draws numbers with as many
+// bits as `n` until one's in range, which takes at most a couple of tries on average.
+#[cfg(feature = "rand")]
+fn random_base<G: rand::Rng + ?Sized>(n: &BigUint, rng: &mut G) -> BigUint {
+    let bits = n.bits();
+    let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+    let lowest = BigUint::from(2u32);
+    let highest = n - 2u32;
+    loop {
+        rng.fill_bytes(&mut bytes);
+        if let Some(top) = bytes.last_mut() {
+            *top &= 0xff >> ((8 - bits % 8) % 8);
+        }
+        let base = BigUint::from_bytes_le(&bytes);
+        if lowest <= base && base <= highest {
+            return base;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_big_values() {
+        let sieve = Sieve::new(1000);
+        let two = BigUint::from(2u32);
+        // 2^521 - 1 is prime, 2^523 - 1 isn't but has no small factors
+        assert!(sieve.is_probable_prime(&(two.pow(521u32) - 1u32), 10));
+        assert!(!sieve.is_probable_prime(&(two.pow(523u32) - 1u32), 10));
+
+        // The product of two primes just below 2^64, which trial division can't find
+        let p = BigUint::from(18_446_744_073_709_551_557u64);
+        let q = BigUint::from(18_446_744_073_709_551_533u64);
+        assert!(!sieve.is_probable_prime(&(&p * &q), 1));
+        assert!(!Sieve::unfilled(10).is_probable_prime(&(&p * &q), 1));
+        assert!(!Sieve::unfilled(10).is_probable_prime(&(&p * 2u32), 1));
+
+        // Small values are exact
+        assert!(sieve.is_probable_prime(&p, 0));
+        assert!(!sieve.is_probable_prime(&BigUint::from(3_215_031_751u64), 0));
+        assert!(!sieve.is_probable_prime(&BigUint::ZERO, 0));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn tests_with_random_bases() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+
+        let mut rng = SmallRng::seed_from_u64(7);
+        let sieve = Sieve::new(1000);
+        let two = BigUint::from(2u32);
+        assert!(sieve.is_probable_prime_with_rng(&(two.pow(521u32) - 1u32), 10, &mut rng));
+        assert!(!sieve.is_probable_prime_with_rng(&(two.pow(523u32) - 1u32), 10, &mut rng));
+
+        let p = BigUint::from(18_446_744_073_709_551_557u64);
+        let q = BigUint::from(18_446_744_073_709_551_533u64);
+        let n = &p * &q;
+        assert!(!sieve.is_probable_prime_with_rng(&n, 1, &mut rng));
+        for _ in 0..1000 {
+            let base = random_base(&n, &mut rng);
+            assert!(base >= two && base <= &n - 2u32);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! A compact table of flags, packed 64 to a word.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "mmap")]
+use crate::mapped::MappedWords;
+
+pub(crate) const WORD_BITS: usize = 64;
+
+/// A fixed-length table of bits, stored packed into `u64`s.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "crate::serde_support::RawBitTable")
+)]
+pub(crate) struct BitTable {
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_support::serialize_words")
+    )]
+    words: Words,
+    len: usize,
+}
+
+/// Where the words of a table are kept
+#[derive(Debug)]
+enum Words {
+    Owned(Vec<u64>),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedWords),
+}
+
+impl Words {
+    // Panics if a mapped file can't be resized, since running out of disk is as fatal here as
+    // running out of memory is for `Vec`
+    fn resize(&mut self, new_len: usize, value: u64) {
+        match self {
+            Words::Owned(words) => words.resize(new_len, value),
+            #[cfg(feature = "mmap")]
+            Words::Mapped(words) => words
+                .resize(new_len, value)
+                .expect("Couldn't resize the file backing this sieve"),
+        }
+    }
+}
+
+impl Deref for Words {
+    type Target = [u64];
+
+    fn deref(&self) -> &[u64] {
+        match self {
+            Words::Owned(words) => words,
+            #[cfg(feature = "mmap")]
+            Words::Mapped(words) => words.as_slice(),
+        }
+    }
+}
+
+impl DerefMut for Words {
+    fn deref_mut(&mut self) -> &mut [u64] {
+        match self {
+            Words::Owned(words) => words,
+            #[cfg(feature = "mmap")]
+            Words::Mapped(words) => words.as_mut_slice(),
+        }
+    }
+}
+
+impl BitTable {
+    /// Create a table of `len` bits, all set.
+    pub(crate) fn ones(len: usize) -> BitTable {
+        let mut table = BitTable {
+            words: Words::Owned(vec![u64::MAX; len.div_ceil(WORD_BITS)]),
+            len,
+        };
+        table.clear_past_end();
+        table
+    }
+
+    /// Create a table of `len` bits, all set, stored in a memory-mapped file at `path`. The file is
+    /// created if it doesn't exist and overwritten if it does.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn ones_mapped(len: usize, path: &std::path::Path) -> std::io::Result<BitTable> {
+        let words = MappedWords::create(path, len.div_ceil(WORD_BITS), u64::MAX)?;
+        let mut table = BitTable {
+            words: Words::Mapped(words),
+            len,
+        };
+        table.clear_past_end();
+        Ok(table)
+    }
+
+    /// Create a table of `len` bits, all clear.
+    pub(crate) fn zeros(len: usize) -> BitTable {
+        BitTable {
+            words: Words::Owned(vec![0; len.div_ceil(WORD_BITS)]),
+            len,
+        }
+    }
+
+    /// Rebuild a table from its words, checking they're the right length for `len` bits and that
+    /// nothing past the end is set.
+    #[cfg(any(feature = "std", feature = "serde"))]
+    pub(crate) fn from_words(words: Vec<u64>, len: usize) -> Option<BitTable> {
+        if words.len() != len.div_ceil(WORD_BITS) {
+            return None;
+        }
+        if !len.is_multiple_of(WORD_BITS) && words.last()? >> (len % WORD_BITS) != 0 {
+            return None;
+        }
+        Some(BitTable {
+            words: Words::Owned(words),
+            len,
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub(crate) fn words_mut(&mut self) -> &mut [u64] {
+        &mut self.words
+    }
+
+    /// The number of bytes of heap memory the words take up, which is none if they're mapped.
+    pub(crate) fn heap_size(&self) -> usize {
+        match &self.words {
+            Words::Owned(words) => words.len() * core::mem::size_of::<u64>(),
+            #[cfg(feature = "mmap")]
+            Words::Mapped(_) => 0,
+        }
+    }
+
+    /// Lengthen the table to `new_len` bits, setting all the new ones.
+    ///
+    /// Has no effect if `new_len` isn't longer than the table already is.
+    pub(crate) fn grow(&mut self, new_len: usize) {
+        if new_len <= self.len {
+            return;
+        }
+        if let Some(last) = self.words.last_mut() {
+            *last |= u64::MAX << (self.len % WORD_BITS);
+        }
+        self.words.resize(new_len.div_ceil(WORD_BITS), u64::MAX);
+        self.len = new_len;
+        self.clear_past_end();
+    }
+
+    /// Shorten the table to `new_len` bits, handing the memory for the dropped words back.
+    ///
+    /// Has no effect if `new_len` isn't shorter than the table already is.
+    pub(crate) fn shrink(&mut self, new_len: usize) {
+        if new_len >= self.len {
+            return;
+        }
+        self.words.resize(new_len.div_ceil(WORD_BITS), 0);
+        match &mut self.words {
+            Words::Owned(words) => words.shrink_to_fit(),
+            #[cfg(feature = "mmap")]
+            Words::Mapped(_) => {}
+        }
+        self.len = new_len;
+        self.clear_past_end();
+    }
+
+    // Clear the unused bits at the top of the last word
+    fn clear_past_end(&mut self) {
+        if !self.len.is_multiple_of(WORD_BITS) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= u64::MAX >> (WORD_BITS - self.len % WORD_BITS);
+            }
+        }
+    }
+
+    // Warning: doesn't check if `i` is out of bounds
+    pub(crate) fn get(&self, i: usize) -> bool {
+        self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0
+    }
+
+    /// Like `get`, but without the bounds check on the words.
+    ///
+    /// # Safety
+    ///
+    /// `i` must be less than the table's length.
+    pub(crate) unsafe fn get_unchecked(&self, i: usize) -> bool {
+        // SAFETY: `i < len`, and there are enough words for `len` bits
+        unsafe { self.words.get_unchecked(i / WORD_BITS) & (1 << (i % WORD_BITS)) != 0 }
+    }
+
+    // Warning: doesn't check if `i` is out of bounds
+    pub(crate) fn clear(&mut self, i: usize) {
+        self.words[i / WORD_BITS] &= !(1 << (i % WORD_BITS));
+    }
+
+    // Warning: doesn't check if `i` is out of bounds
+    pub(crate) fn toggle(&mut self, i: usize) {
+        self.words[i / WORD_BITS] ^= 1 << (i % WORD_BITS);
+    }
+
+    /// Find the index of the first set bit at or after `from`.
+    pub(crate) fn next_set(&self, from: usize) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+        let mut word_index = from / WORD_BITS;
+        let mut word = self.words[word_index] & (u64::MAX << (from % WORD_BITS));
+        loop {
+            if word != 0 {
+                return Some(word_index * WORD_BITS + word.trailing_zeros() as usize);
+            }
+            word_index += 1;
+            word = *self.words.get(word_index)?;
+        }
+    }
+
+    /// Find the index of the last set bit at or before `from`.
+    pub(crate) fn prev_set(&self, from: usize) -> Option<usize> {
+        let from = from.min(self.len.checked_sub(1)?);
+        let mut word_index = from / WORD_BITS;
+        let mut word = self.words[word_index] & (u64::MAX >> (WORD_BITS - 1 - from % WORD_BITS));
+        loop {
+            if word != 0 {
+                return Some(
+                    word_index * WORD_BITS + (WORD_BITS - 1) - word.leading_zeros() as usize,
+                );
+            }
+            word_index = word_index.checked_sub(1)?;
+            word = self.words[word_index];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ones_has_no_trailing_bits() {
+        let table = BitTable::ones(70);
+        assert_eq!(*table.words, [u64::MAX, 0b11_1111]);
+        assert_eq!(table.next_set(70), None);
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "serde"))]
+    fn from_words_validates() {
+        assert!(BitTable::from_words(vec![u64::MAX, 0b11_1111], 70).is_some());
+        assert!(BitTable::from_words(vec![u64::MAX, 0b111_1111], 70).is_none());
+        assert!(BitTable::from_words(vec![u64::MAX], 70).is_none());
+        assert!(BitTable::from_words(vec![], 0).is_some());
+    }
+
+    #[test]
+    fn grow_sets_only_new_bits() {
+        let mut table = BitTable::zeros(10);
+        table.grow(70);
+        assert_eq!(*table.words, [u64::MAX << 10, 0b11_1111]);
+        table.grow(20);
+        assert_eq!(table.len, 70);
+    }
+
+    #[test]
+    fn shrink_drops_words_and_bits() {
+        let mut table = BitTable::ones(200);
+        table.shrink(70);
+        assert_eq!(table.words(), [u64::MAX, 0b11_1111]);
+        table.shrink(100);
+        assert_eq!(table.words().len(), 2);
+        table.shrink(0);
+        assert!(table.words().is_empty());
+    }
+
+    #[test]
+    fn finds_next_set_across_words() {
+        let mut table = BitTable::ones(200);
+        for i in 3..150 {
+            table.clear(i);
+        }
+        assert!(table.get(2));
+        assert!(!table.get(3));
+        assert_eq!(table.next_set(3), Some(150));
+        assert_eq!(table.prev_set(149), Some(2));
+        assert_eq!(table.prev_set(1000), Some(199));
+        table.clear(0);
+        table.clear(1);
+        table.clear(2);
+        assert_eq!(table.prev_set(149), None);
+        assert_eq!(BitTable::zeros(0).prev_set(0), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! A builder for configuring how a sieve is created.
+
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "mmap")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "mmap")]
+use crate::bits::BitTable;
+use crate::Sieve;
+#[cfg(feature = "mmap")]
+use crate::{table_len, Table};
+
+/// Which algorithm fills a full sieve's table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm {
+    /// The Sieve of Eratosthenes, as used by `Sieve::new()`
+    #[default]
+    Eratosthenes,
+    /// The Sieve of Atkin, as used by `Sieve::new_atkin()`
+    Atkin,
+}
+
+/// Why a `SieveBuilder` couldn't create a sieve.
+#[derive(Debug)]
+pub enum BuildError {
+    /// Two of the options can't be used together, e.g. a segment size with the Sieve of Atkin,
+    /// which has no segmented version. Each is named as the builder method that set it.
+    Conflict {
+        first: &'static str,
+        second: &'static str,
+    },
+    /// The memory-mapped file for the table couldn't be created.
+    #[cfg(feature = "mmap")]
+    Io(std::io::Error),
+    /// The thread pool for `threads()` couldn't be started.
+    #[cfg(feature = "rayon")]
+    ThreadPool(rayon::ThreadPoolBuildError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Conflict { first, second } => {
+                write!(f, "{} can't be combined with {}", first, second)
+            }
+            #[cfg(feature = "mmap")]
+            BuildError::Io(e) => write!(f, "Couldn't create the table's file: {}", e),
+            #[cfg(feature = "rayon")]
+            BuildError::ThreadPool(e) => write!(f, "Couldn't start a thread pool: {}", e),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "mmap")]
+            BuildError::Io(e) => Some(e),
+            #[cfg(feature = "rayon")]
+            BuildError::ThreadPool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Configures and creates a `Sieve`, for when the plain constructors aren't enough.
+///
+/// By default this builds the same sieve as `Sieve::new(max)`: a full, bit-packed table in
+/// memory, filled with the Sieve of Eratosthenes. The other storage layouts are a segmented sieve
+/// (`segment_size()`) or, with the `mmap` feature, a table in a memory-mapped file (`mapped()`).
+///
+/// Options that can't be used together are rejected rather than ignored: the Sieve of Atkin has
+/// no segmented, memory-mapped or multi-threaded version, segmented sieves have no table for
+/// threads to fill, and an unfilled sieve has no use for an algorithm or threads. `try_build()`
+/// reports these as a `BuildError`, and `build()` panics on them.
+///
+/// ```
+/// use prime_sieve::{Algorithm, BuildError, Sieve, SieveBuilder};
+///
+/// let atkin = SieveBuilder::new(1000).algorithm(Algorithm::Atkin).build();
+/// assert_eq!(atkin, Sieve::new(1000));
+///
+/// let segmented = SieveBuilder::new(1_000_000).segment_size(1 << 16).build();
+/// assert_eq!(segmented.heap_size(), 168 * 8);
+///
+/// let unfilled = SieveBuilder::new(1000).filled(false).build();
+/// assert!(unfilled.lookup(7).is_err());
+///
+/// let conflicting = SieveBuilder::new(1000)
+///     .algorithm(Algorithm::Atkin)
+///     .segment_size(100)
+///     .try_build();
+/// assert!(matches!(conflicting, Err(BuildError::Conflict { .. })));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SieveBuilder {
+    max: u64,
+    algorithm: Algorithm,
+    segment_size: Option<u64>,
+    #[cfg(feature = "mmap")]
+    path: Option<PathBuf>,
+    #[cfg(feature = "rayon")]
+    threads: Option<usize>,
+    filled: bool,
+}
+
+impl SieveBuilder {
+    /// Start configuring a sieve with the maximum value `max`.
+    pub fn new(max: u64) -> SieveBuilder {
+        SieveBuilder {
+            max,
+            algorithm: Algorithm::default(),
+            segment_size: None,
+            #[cfg(feature = "mmap")]
+            path: None,
+            #[cfg(feature = "rayon")]
+            threads: None,
+            filled: true,
+        }
+    }
+
+    /// Choose the algorithm that fills a full table.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> SieveBuilder {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Make a segmented sieve (see `Sieve::segmented()`) that sieves `segment_size` numbers at a
+    /// time, rather than keeping a full table.
+    ///
+    /// Building panics if `segment_size` is `0`.
+    pub fn segment_size(mut self, segment_size: u64) -> SieveBuilder {
+        self.segment_size = Some(segment_size);
+        self
+    }
+
+    /// Keep the full table in a memory-mapped file at `path` (see `Sieve::mapped()`) rather than
+    /// in memory, replacing anything already there.
+    ///
+    /// ```
+    /// let path = std::env::temp_dir().join("prime_sieve_builder_example.table");
+    /// let my_sieve = prime_sieve::SieveBuilder::new(1_000_000).mapped(&path).build();
+    ///
+    /// assert_eq!(my_sieve.count_primes(), 78_498);
+    /// # drop(my_sieve);
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub fn mapped<P: AsRef<Path>>(mut self, path: P) -> SieveBuilder {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Fill the table using `threads` threads (see `Sieve::fill_parallel()`), or as many as rayon
+    /// likes if `threads` is `0`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::SieveBuilder::new(1_000_000).threads(4).build();
+    /// assert_eq!(my_sieve.count_primes(), 78_498);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn threads(mut self, threads: usize) -> SieveBuilder {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Choose whether to populate the sieve, which is the default. Unfilled sieves can be filled
+    /// later with `Sieve::fill()` and friends.
+    pub fn filled(mut self, filled: bool) -> SieveBuilder {
+        self.filled = filled;
+        self
+    }
+
+    /// Create the sieve, panicking if the options conflict, (with `mapped()`) the table's file
+    /// can't be created or (with `threads()`) the thread pool can't be started - see
+    /// `SieveBuilder::try_build()`.
+    pub fn build(self) -> Sieve {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create the sieve, or return `Err(BuildError::Conflict)` if the options can't be used
+    /// together, `Err(BuildError::Io)` if the table's file (see `mapped()`) can't be created or
+    /// `Err(BuildError::ThreadPool)` if the thread pool (see `threads()`) can't be started.
+    pub fn try_build(self) -> Result<Sieve, BuildError> {
+        self.check_conflicts()?;
+        let mut sieve = match self.segment_size {
+            Some(segment_size) => Sieve::unfilled_segmented(self.max, segment_size),
+            None if self.filled && self.algorithm == Algorithm::Atkin => {
+                return Ok(Sieve::new_atkin(self.max));
+            }
+            None => self.unfilled_full()?,
+        };
+        if !self.filled {
+            return Ok(sieve);
+        }
+        #[cfg(feature = "rayon")]
+        if let Some(threads) = self.threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(BuildError::ThreadPool)?
+                .install(|| sieve.fill_parallel());
+        }
+        // Does nothing if it's already filled
+        sieve.fill();
+        Ok(sieve)
+    }
+
+    // An unfilled full table, in memory or mapped as chosen
+    fn unfilled_full(&self) -> Result<Sieve, BuildError> {
+        #[cfg(feature = "mmap")]
+        if let Some(path) = &self.path {
+            let table = BitTable::ones_mapped(table_len(self.max), path).map_err(BuildError::Io)?;
+            return Ok(Sieve {
+                max: self.max,
+                table: Table::Full(table),
+                filled: false,
+                fill_state: None,
+            });
+        }
+        Ok(Sieve::unfilled(self.max))
+    }
+
+    // The first pair of options found that can't be used together
+    fn check_conflicts(&self) -> Result<(), BuildError> {
+        let conflict = |first, second| Err(BuildError::Conflict { first, second });
+        let atkin = self.algorithm == Algorithm::Atkin;
+        if atkin && !self.filled {
+            return conflict("algorithm(Atkin)", "filled(false)");
+        }
+        if self.segment_size.is_some() && atkin {
+            return conflict("segment_size()", "algorithm(Atkin)");
+        }
+        #[cfg(feature = "mmap")]
+        if self.path.is_some() {
+            if self.segment_size.is_some() {
+                return conflict("mapped()", "segment_size()");
+            }
+            if atkin {
+                return conflict("mapped()", "algorithm(Atkin)");
+            }
+        }
+        #[cfg(feature = "rayon")]
+        if self.threads.is_some() {
+            if atkin {
+                return conflict("threads()", "algorithm(Atkin)");
+            }
+            if self.segment_size.is_some() {
+                return conflict("threads()", "segment_size()");
+            }
+            if !self.filled {
+                return conflict("threads()", "filled(false)");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn builds_equivalent_sieves() {
+        let expected = Sieve::new(100_000);
+        assert_eq!(SieveBuilder::new(100_000).build(), expected);
+        let atkin = SieveBuilder::new(100_000).algorithm(Algorithm::Atkin);
+        assert_eq!(atkin.build(), expected);
+        assert_eq!(
+            SieveBuilder::new(100_000).segment_size(1000).build(),
+            expected
+        );
+        #[cfg(feature = "rayon")]
+        assert_eq!(SieveBuilder::new(100_000).threads(2).build(), expected);
+
+        let mut unfilled = SieveBuilder::new(100_000)
+            .segment_size(1000)
+            .filled(false)
+            .build();
+        assert_eq!(unfilled.get(2), None);
+        unfilled.fill();
+        assert_eq!(unfilled, expected);
+    }
+
+    #[test]
+    fn rejects_conflicting_options() {
+        let atkin = SieveBuilder::new(1000).algorithm(Algorithm::Atkin);
+        let error = atkin.clone().segment_size(100).try_build().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "segment_size() can't be combined with algorithm(Atkin)"
+        );
+        assert!(atkin.filled(false).try_build().is_err());
+        #[cfg(feature = "rayon")]
+        {
+            let threads = SieveBuilder::new(1000).threads(2);
+            assert!(threads.clone().segment_size(100).try_build().is_err());
+            assert!(threads.clone().filled(false).try_build().is_err());
+            let atkin = threads.algorithm(Algorithm::Atkin);
+            assert!(atkin.try_build().is_err());
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn builds_mapped_tables() {
+        let path = std::env::temp_dir().join(format!(
+            "prime_sieve_builder_test_{}.table",
+            std::process::id()
+        ));
+        let expected = Sieve::new(1_000_003);
+        let mapped = SieveBuilder::new(1_000_003).mapped(&path).build();
+        assert_eq!(mapped, expected);
+        drop(mapped);
+        #[cfg(feature = "rayon")]
+        {
+            let threaded = SieveBuilder::new(1_000_003).mapped(&path).threads(2);
+            assert_eq!(threaded.build(), expected);
+        }
+        let mut unfilled = SieveBuilder::new(1_000_003)
+            .mapped(&path)
+            .filled(false)
+            .build();
+        assert!(unfilled.lookup(2).is_err());
+        unfilled.fill();
+        assert_eq!(unfilled, expected);
+        drop(unfilled);
+
+        let segmented = SieveBuilder::new(1000).mapped(&path).segment_size(10);
+        assert!(matches!(
+            segmented.try_build(),
+            Err(BuildError::Conflict { .. })
+        ));
+        let missing = std::env::temp_dir()
+            .join("prime_sieve_no_such_dir")
+            .join("table");
+        let error = SieveBuilder::new(1000).mapped(missing).try_build();
+        assert!(matches!(error, Err(BuildError::Io(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+}
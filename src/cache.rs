@@ -0,0 +1,197 @@
+//! A directory of saved sieves, so repeated runs can reuse earlier work.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{Sieve, Table, CHUNK_WORDS};
+
+/// How many times a fill saves its progress along the way, so an interrupted one can resume
+const CHECKPOINTS: usize = 8;
+
+/// A cache of full sieves saved in a directory, keyed by their max.
+///
+/// Every algorithm gives the same table, so which one made a sieve doesn't matter. Fills save
+/// their progress as they go, so a fill that's interrupted (say, by the program being killed)
+/// picks up from its last checkpoint next time. A sieve that isn't cached yet starts from the
+/// closest filled one that is, truncating a bigger one or extending a smaller one, rather than
+/// from scratch.
+///
+/// ```no_run
+/// let cache = prime_sieve::SieveCache::new("sieve-cache").unwrap();
+///
+/// // Slow the first time, but just a file read after that
+/// let my_sieve = cache.get(10_000_000_000).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SieveCache {
+    dir: PathBuf,
+}
+
+impl SieveCache {
+    /// Use the directory at `dir` as a cache, creating it if it doesn't exist.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<SieveCache> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(SieveCache { dir })
+    }
+
+    /// The directory the cache is kept in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get a filled sieve with the maximum value `max`, from the cache if possible, saving it
+    /// there if it wasn't.
+    ///
+    /// Unreadable or corrupt cache entries are ignored (and eventually overwritten), so only
+    /// failing to save to the cache is an error.
+    pub fn get(&self, max: u64) -> io::Result<Sieve> {
+        self.get_with_progress(max, |_, _| {})
+    }
+
+    /// Like `SieveCache::get()`, but calling `progress(done, total)` while filling the same way
+    /// as `Sieve::fill_with_progress()`. Nothing is reported if the sieve was already cached.
+    pub fn get_with_progress<F: FnMut(u64, u64)>(
+        &self,
+        max: u64,
+        mut progress: F,
+    ) -> io::Result<Sieve> {
+        let path = self.path(max);
+        if let Ok(sieve) = Sieve::read_from(&path) {
+            if sieve.filled && sieve.max == max {
+                return Ok(sieve);
+            }
+            if !sieve.filled && sieve.max == max && sieve.fill_state.is_some() {
+                return self.finish(sieve, &path, progress);
+            }
+        }
+        let sieve = self.closest(max).unwrap_or_else(|| Sieve::unfilled(max));
+        if sieve.filled {
+            self.save(&sieve, &path)?;
+            progress(max, max);
+            return Ok(sieve);
+        }
+        self.finish(sieve, &path, progress)
+    }
+
+    // Fill `sieve`, saving its progress to `path` now and then and once it's done
+    fn finish<F: FnMut(u64, u64)>(
+        &self,
+        mut sieve: Sieve,
+        path: &Path,
+        mut progress: F,
+    ) -> io::Result<Sieve> {
+        let steps = (chunks(&sieve) / CHECKPOINTS).max(1);
+        let mut since_checkpoint = 0;
+        while sieve.fill_chunk(1) {
+            progress(sieve.sieved_up_to(), sieve.max);
+            since_checkpoint += 1;
+            if since_checkpoint == steps {
+                self.save(&sieve, path)?;
+                since_checkpoint = 0;
+            }
+        }
+        progress(sieve.max, sieve.max);
+        self.save(&sieve, path)?;
+        Ok(sieve)
+    }
+
+    // The cached filled sieve nearest to `max`, adjusted to have exactly that max. Bigger ones
+    // are preferred, since truncating them is free.
+    fn closest(&self, max: u64) -> Option<Sieve> {
+        let cached: Vec<u64> = fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|entry| parse_name(entry.ok()?.file_name().to_str()?))
+            .collect();
+        let above = cached.iter().filter(|&&other| other > max).min();
+        let below = cached.iter().filter(|&&other| other < max).max();
+        for &other in above.into_iter().chain(below) {
+            let mut sieve = match Sieve::read_from(self.path(other)) {
+                Ok(sieve) if sieve.filled && sieve.max == other => sieve,
+                _ => continue,
+            };
+            sieve.truncate(max);
+            sieve.extend_to(max);
+            return Some(sieve);
+        }
+        None
+    }
+
+    // Write `sieve` to `path` all at once, so a reader never sees half a file
+    fn save(&self, sieve: &Sieve, path: &Path) -> io::Result<()> {
+        let temporary = self
+            .dir
+            .join(format!(".{}.{}.tmp", sieve.max, std::process::id()));
+        sieve.write_to(&temporary)?;
+        fs::rename(&temporary, path)
+    }
+
+    fn path(&self, max: u64) -> PathBuf {
+        self.dir.join(format!("sieve-{}.sieve", max))
+    }
+}
+
+// How many chunks `Sieve::fill_chunk()` takes to fill `sieve`. Segmented sieves fill in one.
+fn chunks(sieve: &Sieve) -> usize {
+    match &sieve.table {
+        Table::Full(table) => table.words().len().div_ceil(CHUNK_WORDS),
+        Table::Segmented { .. } => 1,
+    }
+}
+
+// The max of the cache entry with the file name `name`, if it is one
+fn parse_name(name: &str) -> Option<u64> {
+    name.strip_prefix("sieve-")?
+        .strip_suffix(".sieve")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn reuses_cached_sieves() {
+        let dir = env::temp_dir().join(format!("prime_sieve_cache_{}", std::process::id()));
+        let cache = SieveCache::new(&dir).unwrap();
+
+        let mut reports = 0;
+        let sieve = cache
+            .get_with_progress(5_000_000, |_, _| reports += 1)
+            .unwrap();
+        assert_eq!(sieve, Sieve::new(5_000_000));
+        assert!(reports > 1);
+
+        // Cached now, and smaller or larger sieves start from it
+        let mut reports = 0;
+        cache
+            .get_with_progress(5_000_000, |_, _| reports += 1)
+            .unwrap();
+        assert_eq!(reports, 0);
+        assert_eq!(cache.get(1000).unwrap(), Sieve::new(1000));
+        assert_eq!(cache.get(6_000_000).unwrap(), Sieve::new(6_000_000));
+        assert!(dir.join("sieve-1000.sieve").exists());
+
+        // An interrupted fill resumes from where it was saved
+        let mut partial = Sieve::unfilled(10_000_000);
+        partial.fill_chunk(6);
+        partial.write_to(dir.join("sieve-10000000.sieve")).unwrap();
+        let mut reports = 0;
+        let resumed = cache
+            .get_with_progress(10_000_000, |_, _| reports += 1)
+            .unwrap();
+        assert_eq!(reports, 5);
+        assert_eq!(resumed, Sieve::new(10_000_000));
+
+        // Corrupt entries are ignored
+        fs::write(dir.join("sieve-77.sieve"), b"garbage").unwrap();
+        assert_eq!(cache.get(77).unwrap(), Sieve::new(77));
+        assert_eq!(parse_name("sieve-12.sieve"), Some(12));
+        assert_eq!(parse_name(".12.34.tmp"), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
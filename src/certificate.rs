@@ -0,0 +1,143 @@
+//! Pratt certificates, which prove that a number is prime in a way anyone can check quickly.
+
+use alloc::vec::Vec;
+
+use crate::modular::pow_mod;
+use crate::Sieve;
+
+/// A proof that `prime` is prime, by Lucas' theorem: `prime` is prime if some `witness` has
+/// `witness^(prime - 1) ≡ 1` but `witness^((prime - 1) / q) ≢ 1 (mod prime)` for every prime
+/// factor `q` of `prime - 1`.
+///
+/// The factors of `prime - 1` come with certificates of their own, so checking the proof with
+/// `PrattCertificate::verify()` doesn't rely on anything else being prime. The fields are public
+/// so that certificates from elsewhere can be checked too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrattCertificate {
+    /// The number proven prime
+    pub prime: u64,
+    /// A primitive root mod `prime`
+    pub witness: u64,
+    /// The prime factorization of `prime - 1`, as each prime's certificate with its exponent
+    pub factors: Vec<(PrattCertificate, u32)>,
+}
+
+impl PrattCertificate {
+    /// Check that this certificate really does prove `self.prime` is prime.
+    ///
+    /// ```
+    /// use prime_sieve::PrattCertificate;
+    ///
+    /// let two = PrattCertificate { prime: 2, witness: 1, factors: vec![] };
+    /// let three = PrattCertificate { prime: 3, witness: 2, factors: vec![(two.clone(), 1)] };
+    /// assert!(three.verify());
+    ///
+    /// // 15 - 1 = 2 * 7, but 7 isn't a primitive root mod 15 (and nor is anything else)
+    /// let seven = prime_sieve::Sieve::new(10).certificate(7).unwrap();
+    /// let fifteen = PrattCertificate { prime: 15, witness: 7, factors: vec![(two, 1), (seven, 1)] };
+    /// assert!(!fifteen.verify());
+    /// ```
+    pub fn verify(&self) -> bool {
+        let p = self.prime;
+        if p < 2 {
+            return false;
+        }
+        let mut product: u64 = 1;
+        for (certificate, exponent) in &self.factors {
+            let q = certificate.prime;
+            if !certificate.verify() || pow_mod(self.witness, (p - 1) / q, p) == 1 {
+                return false;
+            }
+            match q
+                .checked_pow(*exponent)
+                .and_then(|power| product.checked_mul(power))
+            {
+                Some(next) => product = next,
+                None => return false,
+            }
+        }
+        // The factors must account for all of `p - 1`, so none of its prime factors are missed
+        product == p - 1 && pow_mod(self.witness, p - 1, p) == 1
+    }
+}
+
+impl Sieve {
+    /// Produce a `PrattCertificate` proving that `p` is prime.
+    ///
+    /// Returns `None` if `p` isn't prime, or if the sieve can't tell (because it's unfilled or
+    /// `p > sieve.max()`).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    ///
+    /// let certificate = my_sieve.certificate(997).unwrap();
+    /// assert_eq!(certificate.prime, 997);
+    /// assert!(certificate.verify());
+    /// assert_eq!(my_sieve.certificate(999), None);
+    /// ```
+    pub fn certificate(&self, p: u64) -> Option<PrattCertificate> {
+        if !self.lookup(p).ok()? {
+            return None;
+        }
+        // `p - 1` is within the sieve, so it can always be factorized
+        let factorization = self.factorization(p - 1).ok()?;
+        let factors = factorization.factors();
+        let witness = (1..p).find(|&a| {
+            factors
+                .iter()
+                .all(|&(q, _)| pow_mod(a, (p - 1) / q, p) != 1)
+        })?;
+        let factors = factors
+            .iter()
+            .map(|&(q, exponent)| Some((self.certificate(q)?, exponent)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(PrattCertificate {
+            prime: p,
+            witness,
+            factors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certifies_exactly_the_primes() {
+        let sieve = Sieve::new(5000);
+        for n in 0..=5000 {
+            let certificate = sieve.certificate(n);
+            assert_eq!(certificate.is_some(), sieve.lookup(n).unwrap(), "n {}", n);
+            if let Some(certificate) = certificate {
+                assert!(certificate.verify(), "n {}", n);
+            }
+        }
+        assert_eq!(sieve.certificate(5001), None);
+    }
+
+    #[test]
+    fn rejects_forged_certificates() {
+        let sieve = Sieve::new(100);
+        let mut certificate = sieve.certificate(97).unwrap();
+        certificate.witness = 2;
+        assert!(!certificate.verify());
+
+        // Dropping a factor can make a non-primitive root look like one
+        let mut certificate = sieve.certificate(97).unwrap();
+        certificate.factors.pop();
+        assert!(!certificate.verify());
+
+        let mut certificate = sieve.certificate(97).unwrap();
+        certificate.factors[0].0.witness = 0;
+        assert!(!certificate.verify());
+
+        let one = PrattCertificate {
+            prime: 1,
+            witness: 1,
+            factors: Vec::new(),
+        };
+        assert!(!one.verify());
+    }
+}
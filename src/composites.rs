@@ -0,0 +1,357 @@
+//! Classes of numbers defined by their prime factors, rather than by being prime.
+
+use alloc::vec::Vec;
+use core::iter;
+
+use crate::{sqrt_floor, Sieve, SieveError};
+
+/// How many numbers `Sieve::factorized()` factorizes at a time
+const WINDOW: u64 = 1 << 15;
+
+impl Sieve {
+    /// Determine whether `n` is a semiprime, i.e. the product of exactly two (not necessarily
+    /// distinct) primes, like `6`, `9` or `91`.
+    ///
+    /// Only the smallest factor has to be found by trial division - the other is then checked with
+    /// `Sieve::lookup_or_test()`, so `n` can go well beyond `sieve.max()`. Returns the same errors
+    /// as `Sieve::factorize()`, except that `0` isn't an error.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_semiprime(91), Ok(true));
+    /// assert_eq!(my_sieve.is_semiprime(49), Ok(true));
+    /// assert_eq!(my_sieve.is_semiprime(30), Ok(false));
+    /// assert_eq!(my_sieve.is_semiprime(97), Ok(false));
+    /// assert_eq!(my_sieve.is_semiprime(2 * 1_000_000_007), Ok(true));
+    /// ```
+    pub fn is_semiprime(&self, n: u64) -> Result<bool, SieveError> {
+        if !self.filled {
+            return Err(SieveError::NotFilled);
+        }
+        for p in self.primes() {
+            if p > n / p {
+                // `n` is `1` or prime
+                return Ok(false);
+            }
+            if n.is_multiple_of(p) {
+                return Ok(self.lookup_or_test(n / p));
+            }
+        }
+        // If every prime up to `max` was tried, only values below `(max + 1)²` must be prime
+        if u128::from(n) < (u128::from(self.max) + 1).pow(2) {
+            Ok(false)
+        } else {
+            Err(SieveError::OutOfBounds {
+                value: n,
+                max: self.max,
+            })
+        }
+    }
+
+    /// Iterate over every semiprime up to `sieve.max()` (see `Sieve::is_semiprime()`), in
+    /// ascending order.
+    ///
+    /// Rather than trial dividing each number, this counts prime factors as the primes up to
+    /// `√sieve.max()` are strided across a window of numbers at a time, so it only needs a fixed
+    /// amount of memory even on a segmented sieve. Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(30);
+    ///
+    /// let semiprimes: Vec<u64> = my_sieve.semiprimes().collect();
+    /// assert_eq!(semiprimes, vec![4, 6, 9, 10, 14, 15, 21, 22, 25, 26]);
+    /// ```
+    pub fn semiprimes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.factorized(4, |count: &mut u32, _, _, exponent| *count += exponent)
+            .filter(|&(_, count)| count == 2)
+            .map(|(n, _)| n)
+    }
+
+    /// Determine whether `n` is `bound`-smooth, i.e. none of its prime factors are bigger than
+    /// `bound`. `1` is smooth for any bound.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, `Err(SieveError::Zero)` if
+    /// `n == 0`, or `Err(SieveError::OutOfBounds)` if `n` has a factor too large for this sieve to
+    /// tell apart from `bound` (which can only happen when `bound > sieve.max()` and
+    /// `n > sieve.max()²`).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_smooth(2 * 3 * 3 * 7, 7), Ok(true));
+    /// assert_eq!(my_sieve.is_smooth(2 * 3 * 3 * 7, 5), Ok(false));
+    /// assert_eq!(my_sieve.is_smooth(1 << 60, 2), Ok(true));
+    /// ```
+    pub fn is_smooth(&self, n: u64, bound: u64) -> Result<bool, SieveError> {
+        if !self.filled {
+            return Err(SieveError::NotFilled);
+        }
+        if n == 0 {
+            return Err(SieveError::Zero);
+        }
+        let mut remaining = n;
+        for p in self.primes() {
+            if p > remaining / p {
+                // What's left is `1` or prime
+                return Ok(remaining == 1 || remaining <= bound);
+            }
+            if p > bound {
+                return Ok(false);
+            }
+            while remaining.is_multiple_of(p) {
+                remaining /= p;
+            }
+        }
+        if remaining == 1 {
+            Ok(true)
+        } else if bound <= self.max {
+            // Every prime up to `bound` has been divided out
+            Ok(false)
+        } else if u128::from(remaining) < (u128::from(self.max) + 1).pow(2) {
+            Ok(remaining <= bound)
+        } else {
+            Err(SieveError::OutOfBounds {
+                value: n,
+                max: self.max,
+            })
+        }
+    }
+
+    /// Iterate over every `bound`-smooth number from `1` up to `sieve.max()` (see
+    /// `Sieve::is_smooth()`), in ascending order.
+    ///
+    /// Each number's largest prime factor is found by sieving a window of numbers at a time, which
+    /// keeps memory use fixed however big the sieve is. Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(20);
+    ///
+    /// let smooth: Vec<u64> = my_sieve.smooth_numbers(3).collect();
+    /// assert_eq!(smooth, vec![1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
+    /// ```
+    pub fn smooth_numbers(&self, bound: u64) -> impl Iterator<Item = u64> + '_ {
+        // Factors arrive smallest first, so the last one is the largest
+        self.factorized(1, |largest: &mut u64, _, p, _| *largest = p)
+            .filter(move |&(_, largest)| largest <= bound)
+            .map(|(n, _)| n)
+    }
+
+    /// Determine whether `n` is a Carmichael number, i.e. a composite that passes the Fermat test
+    /// `a^(n - 1) ≡ 1 (mod n)` for every base `a` coprime to it, like `561 = 3 · 11 · 17`.
+    ///
+    /// This uses Korselt's criterion: `n` is a Carmichael number exactly when it's composite,
+    /// squarefree, and `p - 1` divides `n - 1` for every prime `p` dividing it. Returns the same
+    /// errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_carmichael(561), Ok(true));
+    /// assert_eq!(my_sieve.is_carmichael(563), Ok(false));
+    /// ```
+    pub fn is_carmichael(&self, n: u64) -> Result<bool, SieveError> {
+        let factors = self.factorize(n)?;
+        Ok(factors.len() > 1
+            && factors.windows(2).all(|pair| pair[0] != pair[1])
+            && factors.iter().all(|&p| (n - 1).is_multiple_of(p - 1)))
+    }
+
+    /// Iterate over every Carmichael number up to `sieve.max()` (see `Sieve::is_carmichael()`),
+    /// in ascending order.
+    ///
+    /// Korselt's criterion is checked against each prime power as it's sieved out of a window of
+    /// numbers, rather than factorizing each number by trial division, and the window keeps
+    /// memory use fixed. Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(10_000);
+    ///
+    /// let carmichael: Vec<u64> = my_sieve.carmichael_numbers().collect();
+    /// assert_eq!(carmichael, vec![561, 1105, 1729, 2465, 2821, 6601, 8911]);
+    /// ```
+    pub fn carmichael_numbers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.factorized(3, |korselt: &mut Korselt, n, p, exponent| {
+            korselt.factors += 1;
+            korselt.fails |= exponent > 1 || !(n - 1).is_multiple_of(p - 1);
+        })
+        .filter(|&(n, korselt)| n % 2 == 1 && korselt.factors > 1 && !korselt.fails)
+        .map(|(n, _)| n)
+    }
+
+    // Every number from `first` (which must be at least 1) up to `max` with the state built up by
+    // calling `add(state, n, p, exponent)` for each prime power `p^exponent` exactly dividing it,
+    // smallest first. A window of numbers is factorized at a time by striding the primes up to
+    // `√max` across it; whatever's left of a number after that is 1 or one more prime. Yields
+    // nothing if the sieve is unfilled.
+    fn factorized<S, F>(&self, first: u64, mut add: F) -> impl Iterator<Item = (u64, S)> + '_
+    where
+        S: Default + 'static,
+        F: FnMut(&mut S, u64, u64, u32) + 'static,
+    {
+        let root = sqrt_floor(self.max);
+        let base_primes: Vec<u64> = if self.filled {
+            self.primes().take_while(|&p| p <= root).collect()
+        } else {
+            Vec::new()
+        };
+        let max = self.max;
+        let mut next = Some(first).filter(|_| self.filled);
+        iter::from_fn(move || {
+            let start = next.filter(|&start| start <= max)?;
+            let end = start.saturating_add(WINDOW - 1).min(max);
+            next = end.checked_add(1);
+
+            let mut rest: Vec<u64> = (start..=end).collect();
+            let mut states: Vec<S> = rest.iter().map(|_| S::default()).collect();
+            for &p in &base_primes {
+                if p > end / p {
+                    break;
+                }
+                let mut i = ((p - start % p) % p) as usize;
+                while i < rest.len() {
+                    let mut exponent = 0;
+                    while rest[i].is_multiple_of(p) {
+                        rest[i] /= p;
+                        exponent += 1;
+                    }
+                    add(&mut states[i], start + i as u64, p, exponent);
+                    i += p as usize;
+                }
+            }
+            for (i, (&left, state)) in rest.iter().zip(&mut states).enumerate() {
+                if left > 1 {
+                    add(state, start + i as u64, left, 1);
+                }
+            }
+            Some((start..=end).zip(states))
+        })
+        .flatten()
+    }
+}
+
+// What `Sieve::carmichael_numbers()` keeps track of while checking Korselt's criterion
+#[derive(Debug, Clone, Copy, Default)]
+struct Korselt {
+    factors: u32,
+    // Whether some prime's square divides `n`, or `p - 1` doesn't divide `n - 1` for some `p`
+    fails: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semiprimes_have_two_factors() {
+        let sieve = Sieve::new(10_000);
+        for n in 0..=20_000 {
+            let expected = n > 0 && sieve.factorize(n).unwrap().len() == 2;
+            assert_eq!(sieve.is_semiprime(n), Ok(expected), "n {}", n);
+        }
+        // There are 2625 semiprimes below 10^4
+        assert_eq!(sieve.semiprimes().count(), 2625);
+        assert!(sieve
+            .semiprimes()
+            .eq((0..=10_000).filter(|&n| sieve.is_semiprime(n) == Ok(true))));
+        assert_eq!(Sieve::new(4).semiprimes().collect::<Vec<u64>>(), vec![4]);
+        assert_eq!(Sieve::new(3).semiprimes().count(), 0);
+        assert_eq!(Sieve::unfilled(100).semiprimes().count(), 0);
+
+        // Across several windows, and on a segmented sieve with no table to speak of
+        let big = Sieve::new(100_000);
+        assert!(big
+            .semiprimes()
+            .eq(Sieve::segmented(100_000, 1000).semiprimes()));
+        assert!(big
+            .semiprimes()
+            .eq((0..=100_000).filter(|&n| big.is_semiprime(n) == Ok(true))));
+        let huge = Sieve::segmented(10_000_000_000, 1 << 16);
+        assert_eq!(
+            huge.semiprimes().take(3).collect::<Vec<u64>>(),
+            vec![4, 6, 9]
+        );
+
+        let small = Sieve::new(10);
+        assert_eq!(small.is_semiprime(120), Ok(false));
+        assert_eq!(small.is_semiprime(113), Ok(false));
+        // This needs a factor bigger than the sieve to tell
+        assert_eq!(
+            small.is_semiprime(11 * 13),
+            Err(SieveError::OutOfBounds {
+                value: 11 * 13,
+                max: 10
+            })
+        );
+        assert_eq!(
+            Sieve::unfilled(10).is_semiprime(4),
+            Err(SieveError::NotFilled)
+        );
+    }
+
+    #[test]
+    fn smooth_numbers_have_small_factors() {
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            let largest = sieve.factorize(n).unwrap().last().copied().unwrap_or(0);
+            for bound in [0, 1, 2, 5, 7, 50, 97, 100, 10_000] {
+                assert_eq!(
+                    sieve.is_smooth(n, bound),
+                    Ok(largest <= bound),
+                    "n {} bound {}",
+                    n,
+                    bound
+                );
+            }
+        }
+        assert_eq!(sieve.smooth_numbers(1).collect::<Vec<u64>>(), vec![1]);
+        assert_eq!(sieve.smooth_numbers(100).count(), 100);
+        for bound in [0, 2, 3, 7, 50] {
+            assert!(sieve
+                .smooth_numbers(bound)
+                .eq((1..=100).filter(|&n| sieve.is_smooth(n, bound) == Ok(true))));
+        }
+        assert_eq!(Sieve::unfilled(100).smooth_numbers(5).count(), 0);
+        let big = Sieve::new(100_000);
+        assert!(big
+            .smooth_numbers(7)
+            .eq(Sieve::segmented(100_000, 1000).smooth_numbers(7)));
+        assert!(big
+            .smooth_numbers(300)
+            .eq((1..=100_000).filter(|&n| big.is_smooth(n, 300) == Ok(true))));
+
+        // Big factors are fine as long as the bound rules them out
+        assert_eq!(sieve.is_smooth(2 * 1_000_000_007, 100), Ok(false));
+        assert_eq!(
+            sieve.is_smooth(1_000_000_007, 2_000_000_000),
+            Err(SieveError::OutOfBounds {
+                value: 1_000_000_007,
+                max: 100,
+            })
+        );
+        assert_eq!(sieve.is_smooth(0, 5), Err(SieveError::Zero));
+    }
+
+    #[test]
+    fn carmichael_numbers_fool_fermat() {
+        use crate::modular::{gcd, pow_mod};
+
+        let sieve = Sieve::new(100_000);
+        let fools_fermat = |n: u64| (2..n).all(|a| gcd(a, n) != 1 || pow_mod(a, n - 1, n) == 1);
+        for n in 2..=3000 {
+            let expected = !sieve.lookup(n).unwrap() && fools_fermat(n);
+            assert_eq!(sieve.is_carmichael(n), Ok(expected), "n {}", n);
+        }
+        // There are 16 Carmichael numbers below 10^5
+        assert_eq!(sieve.carmichael_numbers().count(), 16);
+        assert!(sieve
+            .carmichael_numbers()
+            .eq((0..=100_000).filter(|&n| sieve.is_carmichael(n) == Ok(true))));
+        assert_eq!(sieve.is_carmichael(1), Ok(false));
+        assert_eq!(sieve.is_carmichael(0), Err(SieveError::Zero));
+        assert_eq!(Sieve::unfilled(1000).carmichael_numbers().count(), 0);
+        assert!(sieve
+            .carmichael_numbers()
+            .eq(Sieve::segmented(100_000, 1000).carmichael_numbers()));
+    }
+}
@@ -0,0 +1,52 @@
+//! Sieving at compile time, for small lookup tables baked into the binary.
+
+/// Build a table of whether each number in `0..N` is prime, as a `const fn` so it can be
+/// evaluated at compile time.
+///
+/// This is a plain sieve of Eratosthenes with one `bool` per number, so it's best kept to small
+/// tables - the compiler evaluates const code far slower than it runs, and warns about very long
+/// evaluations. Use `Sieve` for anything big.
+///
+/// ```
+/// const PRIMES: [bool; 100] = prime_sieve::const_sieve::<100>();
+///
+/// assert!(PRIMES[97]);
+/// assert!(!PRIMES[91]);
+/// assert!(!PRIMES[1]);
+/// ```
+pub const fn const_sieve<const N: usize>() -> [bool; N] {
+    let mut table = [true; N];
+    let mut i = 0;
+    while i < N && i < 2 {
+        table[i] = false;
+        i += 1;
+    }
+    while i * i < N {
+        if table[i] {
+            let mut multiple = i * i;
+            while multiple < N {
+                table[multiple] = false;
+                multiple += i;
+            }
+        }
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sieve;
+
+    #[test]
+    fn matches_sieve() {
+        const TABLE: [bool; 1000] = const_sieve::<1000>();
+        let sieve = Sieve::new(999);
+        for (n, &prime) in TABLE.iter().enumerate() {
+            assert_eq!(prime, sieve.lookup(n as u64).unwrap(), "n {}", n);
+        }
+        assert_eq!(const_sieve::<0>(), [false; 0]);
+        assert_eq!(const_sieve::<3>(), [false, false, true]);
+    }
+}
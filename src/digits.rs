@@ -0,0 +1,231 @@
+//! Classes of primes defined by their digits.
+
+use core::convert::TryFrom;
+
+use crate::{Sieve, SieveError};
+
+impl Sieve {
+    /// Iterate over every prime in the sieve that's a palindrome when written in base `radix`
+    /// (use `10` for decimal), in ascending order.
+    ///
+    /// Only the palindromes are looked up, so this is much quicker than checking every prime.
+    /// Yields nothing if the sieve is unfilled. Panics if `radix < 2`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(200);
+    ///
+    /// let decimal: Vec<u64> = my_sieve.palindromic_primes(10).collect();
+    /// assert_eq!(decimal, vec![2, 3, 5, 7, 11, 101, 131, 151, 181, 191]);
+    ///
+    /// // 0b11, 0b101, 0b111, 0b10001, 0b11111
+    /// let binary: Vec<u64> = my_sieve.palindromic_primes(2).take(5).collect();
+    /// assert_eq!(binary, vec![3, 5, 7, 17, 31]);
+    /// ```
+    pub fn palindromic_primes(&self, radix: u32) -> impl Iterator<Item = u64> + '_ {
+        assert!(radix >= 2, "Radix must be at least 2");
+        let radix = u64::from(radix);
+        let max = if self.filled { self.max } else { 0 };
+        (1..=digit_count(max, radix))
+            .flat_map(move |len| {
+                // Every palindrome of this length, in order, from the first half of its digits
+                let half = len.div_ceil(2);
+                let first = radix.pow(half - 1);
+                let last = radix.checked_pow(half).unwrap_or(u64::MAX);
+                (first..last).map_while(move |prefix| mirror(prefix, len, radix))
+            })
+            .take_while(move |&n| n <= max)
+            .filter(move |&n| self.is_prime_unchecked(n))
+    }
+
+    /// Determine whether `n` is a circular prime, i.e. every rotation of its decimal digits is
+    /// prime (like `197`, `971` and `719`).
+    ///
+    /// Rotations beyond `sieve.max()`, or every rotation if the sieve is unfilled, are checked with
+    /// a Baillie-PSW test instead. Returns `Err(SieveError::Overflow)` if a rotation needs
+    /// checking but is too big for a `u64`, which can only happen when `n` has 20 digits.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    ///
+    /// assert_eq!(my_sieve.is_circular_prime(197), Ok(true));
+    /// assert_eq!(my_sieve.is_circular_prime(19), Ok(false));
+    /// assert_eq!(my_sieve.is_circular_prime(199_933), Ok(true));
+    /// ```
+    pub fn is_circular_prime(&self, n: u64) -> Result<bool, SieveError> {
+        let digits = digit_count(n, 10);
+        // Some rotation of anything else ends in an even digit or a 5
+        if digits > 1 && !decimal_digits(n).all(|digit| [1, 3, 7, 9].contains(&digit)) {
+            return Ok(false);
+        }
+        let top = 10u128.pow(digits - 1);
+        let mut rotation = u128::from(n);
+        for _ in 0..digits {
+            let candidate = u64::try_from(rotation).map_err(|_| SieveError::Overflow)?;
+            if !self.lookup_or_test(candidate) {
+                return Ok(false);
+            }
+            rotation = rotation % top * 10 + rotation / top;
+        }
+        Ok(true)
+    }
+
+    /// Determine whether `n` is an emirp, i.e. a prime whose decimal digits reversed make a
+    /// *different* prime (like `13` and `31`, but not `11`).
+    ///
+    /// Like `Sieve::is_circular_prime()`, anything beyond `sieve.max()` is checked with a
+    /// Baillie-PSW test instead, and `Err(SieveError::Overflow)` means the reversal of `n` is too
+    /// big for a `u64`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_emirp(13), Ok(true));
+    /// assert_eq!(my_sieve.is_emirp(11), Ok(false));
+    /// assert_eq!(my_sieve.is_emirp(23), Ok(false));
+    /// assert_eq!(my_sieve.is_emirp(1009), Ok(true));
+    /// ```
+    pub fn is_emirp(&self, n: u64) -> Result<bool, SieveError> {
+        if !self.lookup_or_test(n) {
+            return Ok(false);
+        }
+        let reversed = reverse_digits(n).ok_or(SieveError::Overflow)?;
+        Ok(reversed != n && self.lookup_or_test(reversed))
+    }
+
+    /// Iterate over every emirp in the sieve (see `Sieve::is_emirp()`), in ascending order.
+    ///
+    /// Yields nothing if the sieve is unfilled. Primes whose reversals are too big to check are
+    /// left out, though they only exist in sieves with twenty-digit maxima.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let emirps: Vec<u64> = my_sieve.emirps().collect();
+    /// assert_eq!(emirps, vec![13, 17, 31, 37, 71, 73, 79, 97]);
+    /// ```
+    pub fn emirps(&self) -> impl Iterator<Item = u64> + '_ {
+        self.primes().filter(move |&p| self.is_emirp(p) == Ok(true))
+    }
+}
+
+// `n` with its decimal digits reversed, or `None` if that's too big for a `u64`
+fn reverse_digits(n: u64) -> Option<u64> {
+    decimal_digits(n).try_fold(0u64, |reversed, digit| {
+        reversed.checked_mul(10)?.checked_add(digit)
+    })
+}
+
+// The decimal digits of `n`, least significant first
+fn decimal_digits(mut n: u64) -> impl Iterator<Item = u64> {
+    let mut done = false;
+    core::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let digit = n % 10;
+        n /= 10;
+        done = n == 0;
+        Some(digit)
+    })
+}
+
+// The number of digits in `n` written in base `radix`
+fn digit_count(mut n: u64, radix: u64) -> u32 {
+    let mut count = 1;
+    while n >= radix {
+        n /= radix;
+        count += 1;
+    }
+    count
+}
+
+// The palindrome with `len` digits whose first half (rounded up) is `prefix`, or `None` if it's
+// too big for a `u64`
+fn mirror(prefix: u64, len: u32, radix: u64) -> Option<u64> {
+    let mut result = prefix;
+    let mut rest = if len % 2 == 1 { prefix / radix } else { prefix };
+    while rest > 0 {
+        result = result.checked_mul(radix)?.checked_add(rest % radix)?;
+        rest /= radix;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn is_palindrome(n: u64, radix: u64) -> bool {
+        let mut digits = Vec::new();
+        let mut rest = n;
+        while rest > 0 {
+            digits.push(rest % radix);
+            rest /= radix;
+        }
+        digits.iter().eq(digits.iter().rev())
+    }
+
+    #[test]
+    fn finds_every_palindromic_prime() {
+        let sieve = Sieve::new(1_000_000);
+        for radix in [2, 3, 10, 16] {
+            let expected: Vec<u64> = sieve
+                .primes()
+                .filter(|&p| is_palindrome(p, radix))
+                .collect();
+            let found: Vec<u64> = sieve.palindromic_primes(radix as u32).collect();
+            assert_eq!(found, expected, "radix {}", radix);
+        }
+        assert_eq!(Sieve::unfilled(100).palindromic_primes(10).count(), 0);
+        assert_eq!(mirror(u64::MAX / 10, 39, 10), None);
+    }
+
+    #[test]
+    fn finds_circular_primes() {
+        let sieve = Sieve::new(1_000_000);
+        // There are 55 circular primes below 10^6
+        let circular: Vec<u64> = sieve
+            .primes()
+            .filter(|&p| sieve.is_circular_prime(p).unwrap())
+            .collect();
+        assert_eq!(circular.len(), 55);
+        assert_eq!(
+            circular[..13],
+            [2, 3, 5, 7, 11, 13, 17, 31, 37, 71, 73, 79, 97]
+        );
+
+        // Rotations beyond the sieve are tested instead
+        let small = Sieve::new(100);
+        assert_eq!(small.is_circular_prime(1_111_111_111_111_111_111), Ok(true));
+        assert_eq!(small.is_circular_prime(193_939), Ok(true));
+        assert_eq!(small.is_circular_prime(193_937), Ok(false));
+        assert_eq!(small.is_circular_prime(0), Ok(false));
+        assert_eq!(
+            small.is_circular_prime(13_111_111_111_111_111_799),
+            Err(SieveError::Overflow)
+        );
+    }
+
+    #[test]
+    fn finds_emirps() {
+        // There are 240 emirps below 10^4
+        let sieve = Sieve::new(10_000);
+        assert_eq!(sieve.emirps().count(), 240);
+        for p in sieve.emirps() {
+            let reversed = reverse_digits(p).unwrap();
+            assert!(
+                sieve.is_emirp(reversed).unwrap() || reversed > 10_000,
+                "{}",
+                p
+            );
+        }
+        assert_eq!(reverse_digits(1200), Some(21));
+        assert_eq!(reverse_digits(u64::MAX), None);
+        assert_eq!(sieve.is_emirp(1_000_000_007), Ok(true));
+        assert_eq!(
+            sieve.is_emirp(18_446_744_073_709_551_557),
+            Err(SieveError::Overflow)
+        );
+    }
+}
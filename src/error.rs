@@ -0,0 +1,48 @@
+//! The error type returned by sieve queries.
+
+use core::error::Error;
+use core::fmt;
+
+/// Why a sieve couldn't answer a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SieveError {
+    /// The sieve hasn't been populated yet - see `Sieve::fill()`.
+    NotFilled,
+    /// `value` is larger than the max value of the sieve.
+    OutOfBounds { value: u64, max: u64 },
+    /// `value` is smaller than the lowest number a slice or window of a sieve covers.
+    BelowRange { value: u64, min: u64 },
+    /// `0` was passed to a query that's only defined for positive numbers.
+    Zero,
+    /// `value` was expected to be prime, but isn't.
+    NotPrime { value: u64 },
+    /// The answer is too large to fit in the query's return type.
+    Overflow,
+    /// A list of primes to build a sieve from was out of order, or was missing some of the
+    /// primes it should contain.
+    InvalidPrimes,
+}
+
+impl fmt::Display for SieveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SieveError::NotFilled => write!(f, "Sieve not populated!"),
+            SieveError::OutOfBounds { value, max } => {
+                write!(f, "{} is out of this sieve's bounds (max {})", value, max)
+            }
+            SieveError::BelowRange { value, min } => {
+                write!(
+                    f,
+                    "{} is below the start of this range (min {})",
+                    value, min
+                )
+            }
+            SieveError::Zero => write!(f, "0 is not a valid input here"),
+            SieveError::NotPrime { value } => write!(f, "{} is not prime", value),
+            SieveError::Overflow => write!(f, "Result is too large to represent"),
+            SieveError::InvalidPrimes => write!(f, "List of primes is out of order or incomplete"),
+        }
+    }
+}
+
+impl Error for SieveError {}
@@ -0,0 +1,200 @@
+//! Approximations to the prime-counting function π(x), for comparison with exact counts, and
+//! Chebyshev's functions θ(x) and ψ(x).
+
+use crate::{Sieve, SieveError};
+
+/// The Euler–Mascheroni constant γ
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// The logarithmic integral li(x), the integral of `1 / ln t` from `0` to `x` (taking the
+/// principal value across the singularity at `1`).
+///
+/// This is the better of the two estimates of π(x) given by the prime number theorem. li(0) is
+/// `0`, li(1) is `-∞` and negative values of `x` give `NaN`.
+///
+/// ```
+/// use prime_sieve::estimates::li;
+///
+/// assert!((li(1_000_000.0) - 78_627.549).abs() < 0.001);
+/// assert!((li(2.0) - 1.045_163_780).abs() < 1e-9);
+/// ```
+pub fn li(x: f64) -> f64 {
+    if x == 0.0 {
+        return 0.0;
+    }
+    // li(x) = Ei(ln x), summing the series Ei(y) = γ + ln |y| + Σ yⁿ / (n · n!)
+    let y = x.ln();
+    let mut sum = EULER_GAMMA + y.abs().ln();
+    let mut term = 1.0;
+    for n in 1.. {
+        term *= y / f64::from(n);
+        let contribution = term / f64::from(n);
+        sum += contribution;
+        if contribution.abs() <= sum.abs() * f64::EPSILON {
+            break;
+        }
+    }
+    sum
+}
+
+/// The estimate `x / ln x` of π(x) from the prime number theorem.
+///
+/// ```
+/// use prime_sieve::estimates::x_over_ln_x;
+///
+/// assert!((x_over_ln_x(1_000_000.0) - 72_382.414).abs() < 0.001);
+/// ```
+pub fn x_over_ln_x(x: f64) -> f64 {
+    x / x.ln()
+}
+
+/// An upper bound on the `n`th prime (counting from 1), from Rosser's `pₙ < n (ln n + ln ln n)`
+/// for `n >= 6`, so that a sieve with this max is certain to contain it.
+///
+/// Gives `0` for `n == 0`, and saturates at `u64::MAX` when `n` is too big for the bound to fit.
+///
+/// ```
+/// use prime_sieve::estimates::nth_prime_upper_bound;
+///
+/// let bound = nth_prime_upper_bound(1_000_000);
+/// assert!(bound >= 15_485_863);
+/// assert!(bound < 16_500_000);
+/// assert_eq!(prime_sieve::Sieve::new(bound).nth_prime(1_000_000), Some(15_485_863));
+/// ```
+pub fn nth_prime_upper_bound(n: u64) -> u64 {
+    const SMALL: [u64; 6] = [0, 2, 3, 5, 7, 11];
+    if n < 6 {
+        return SMALL[n as usize];
+    }
+    let n = n as f64;
+    // Rounding up (as casting saturates) covers the error in the logarithms
+    (n * (n.ln() + n.ln().ln())).ceil() as u64
+}
+
+/// The exact value of π(x) alongside its estimates, for some `x`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountEstimates {
+    /// The true number of primes `<= x`
+    pub exact: u64,
+    /// `li(x)`
+    pub li: f64,
+    /// `x / ln x`
+    pub x_over_ln_x: f64,
+}
+
+impl Sieve {
+    /// Compare the number of primes in this sieve with the estimates of it given by the prime
+    /// number theorem, taking `x` to be `sieve.max()`.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if the sieve is unpopulated.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1_000_000);
+    /// let estimates = my_sieve.count_vs_estimates().unwrap();
+    ///
+    /// assert_eq!(estimates.exact, 78_498);
+    /// assert!(estimates.x_over_ln_x < estimates.exact as f64);
+    /// assert!(estimates.li > estimates.exact as f64);
+    /// ```
+    pub fn count_vs_estimates(&self) -> Result<CountEstimates, SieveError> {
+        if !self.filled {
+            return Err(SieveError::NotFilled);
+        }
+        let x = self.max as f64;
+        Ok(CountEstimates {
+            exact: self.count_primes(),
+            li: li(x),
+            x_over_ln_x: x_over_ln_x(x),
+        })
+    }
+
+    /// Compute Chebyshev's function θ(x), the sum of `ln p` over every prime `p <= x`.
+    ///
+    /// Returns the same errors as `Sieve::lookup()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert!((my_sieve.theta(100).unwrap() - 83.728_390_399).abs() < 1e-9);
+    /// assert_eq!(my_sieve.theta(1), Ok(0.0));
+    /// ```
+    pub fn theta(&self, x: u64) -> Result<f64, SieveError> {
+        self.lookup(x)?;
+        Ok(self.primes_in_range(..=x).map(|p| (p as f64).ln()).sum())
+    }
+
+    /// Compute Chebyshev's function ψ(x), the sum of `ln p` over every prime power `pᵏ <= x`.
+    ///
+    /// Returns the same errors as `Sieve::lookup()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert!((my_sieve.psi(100).unwrap() - 94.045_311_229).abs() < 1e-9);
+    /// ```
+    pub fn psi(&self, x: u64) -> Result<f64, SieveError> {
+        self.lookup(x)?;
+        Ok(self
+            .primes_in_range(..=x)
+            .map(|p| {
+                // Count the powers of `p` up to `x`, which is just `p` itself past `√x`
+                let mut powers = 1;
+                let mut power = p;
+                while let Some(next) = power.checked_mul(p).filter(|&next| next <= x) {
+                    powers += 1;
+                    power = next;
+                }
+                f64::from(powers) * (p as f64).ln()
+            })
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::LN_2;
+
+    #[test]
+    fn li_known_values() {
+        // li(x) has its only positive root at the Ramanujan–Soldner constant
+        assert!(li(1.451_369_234_883_381).abs() < 1e-12);
+        assert!((li(0.5) + 0.378_671_043).abs() < 1e-9);
+        assert!((li(1e18) - 24_739_954_309_690_414.0).abs() < 1e3);
+        assert_eq!(li(1.0), f64::NEG_INFINITY);
+        assert!((x_over_ln_x(2.0) - 2.0 / LN_2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn nth_prime_bounds_hold() {
+        let sieve = Sieve::new(2_000_000);
+        for (i, p) in sieve.primes().enumerate() {
+            let n = i as u64 + 1;
+            assert!(nth_prime_upper_bound(n) >= p, "n {}", n);
+        }
+        assert_eq!(nth_prime_upper_bound(0), 0);
+        assert_eq!(nth_prime_upper_bound(1), 2);
+        assert_eq!(nth_prime_upper_bound(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn chebyshev_functions() {
+        let sieve = Sieve::new(1_000_000);
+        assert!((sieve.theta(1_000_000).unwrap() - 998_484.175_025_634).abs() < 1e-6);
+        // ψ(x) adds θ(x^(1/k)) for every k, and is ln lcm(1..=x)
+        let expected = sieve.theta(1_000_000).unwrap()
+            + sieve.theta(1000).unwrap()
+            + (2..20)
+                .map(|k| {
+                    sieve
+                        .theta((1_000_000f64.powf(1.0 / (k + 1) as f64) + 1e-9) as u64)
+                        .unwrap()
+                })
+                .sum::<f64>();
+        assert!((sieve.psi(1_000_000).unwrap() - expected).abs() < 1e-6);
+        assert!((sieve.psi(10).unwrap() - 2520f64.ln()).abs() < 1e-12);
+        assert_eq!(sieve.theta(0), Ok(0.0));
+        assert_eq!(sieve.psi(1), Ok(0.0));
+        assert!(sieve.psi(1_000_001).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! A sieve that remembers the smallest prime factor of every number, for fast factorization.
+
+use alloc::vec::Vec;
+
+use crate::{linear, SieveError};
+
+/// A sieve recording the smallest prime factor of every number up to its max.
+///
+/// This takes 4 bytes per number rather than `Sieve`'s fraction of a bit, but lets any value up
+/// to the max be factorized in `O(log n)` time.
+///
+/// ```
+/// let factors = prime_sieve::FactorSieve::new(1000);
+///
+/// assert_eq!(factors.smallest_factor(91).unwrap(), 7);
+/// assert_eq!(factors.factorize(360).unwrap(), vec![2, 2, 2, 3, 3, 5]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FactorSieve {
+    max: u64,
+    // The smallest prime factor of each composite (which always fits in a `u32`, being at most
+    // `√max`), or `0` for primes, `0` and `1`
+    smallest_factors: Vec<u32>,
+}
+
+impl FactorSieve {
+    /// Create and populate a factor sieve with the maximum value `max`, using the linear sieve so
+    /// that each number is only visited once.
+    pub fn new(max: u64) -> FactorSieve {
+        FactorSieve {
+            max,
+            smallest_factors: linear::smallest_factors(max),
+        }
+    }
+
+    /// Get the max value of this sieve
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    fn check(&self, n: u64) -> Result<(), SieveError> {
+        if n == 0 {
+            Err(SieveError::Zero)
+        } else if n > self.max {
+            Err(SieveError::OutOfBounds {
+                value: n,
+                max: self.max,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    // Warning: doesn't check if `n` is out of bounds
+    fn smallest_factor_unchecked(&self, n: u64) -> u64 {
+        match self.smallest_factors[n as usize] {
+            0 => n,
+            p => u64::from(p),
+        }
+    }
+
+    /// Determine whether a number within the sieve's limits is prime or not.
+    ///
+    /// Returns `Err(SieveError::OutOfBounds)` if `n > sieve.max()`.
+    pub fn lookup(&self, n: u64) -> Result<bool, SieveError> {
+        if n < 2 {
+            return self.check(n.max(1)).map(|_| false);
+        }
+        self.check(n)?;
+        Ok(self.smallest_factors[n as usize] == 0)
+    }
+
+    /// Get the smallest prime factor of `n`. By convention the smallest factor of `1` is `1`.
+    ///
+    /// Returns `Err(SieveError::Zero)` if `n == 0`, or `Err(SieveError::OutOfBounds)` if
+    /// `n > sieve.max()`.
+    pub fn smallest_factor(&self, n: u64) -> Result<u64, SieveError> {
+        self.check(n)?;
+        Ok(self.smallest_factor_unchecked(n))
+    }
+
+    /// Find the prime factorization of `n`, in ascending order and with multiplicity.
+    /// `factorize(1)` is empty.
+    ///
+    /// Returns `Err(SieveError::Zero)` if `n == 0`, or `Err(SieveError::OutOfBounds)` if
+    /// `n > sieve.max()`.
+    pub fn factorize(&self, n: u64) -> Result<Vec<u64>, SieveError> {
+        self.check(n)?;
+        let mut factors = Vec::new();
+        let mut remaining = n;
+        while remaining > 1 {
+            let p = self.smallest_factor_unchecked(remaining);
+            factors.push(p);
+            remaining /= p;
+        }
+        Ok(factors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sieve;
+
+    #[test]
+    fn matches_sieve() {
+        let factors = FactorSieve::new(10_000);
+        let sieve = Sieve::new(10_000);
+        for n in 1..=10_000 {
+            assert_eq!(factors.lookup(n), sieve.lookup(n));
+            assert_eq!(factors.factorize(n), sieve.factorize(n));
+        }
+        assert_eq!(factors.lookup(0), Ok(false));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let factors = FactorSieve::new(10);
+        assert_eq!(factors.smallest_factor(0), Err(SieveError::Zero));
+        assert_eq!(factors.smallest_factor(1), Ok(1));
+        assert_eq!(
+            factors.factorize(11),
+            Err(SieveError::OutOfBounds { value: 11, max: 10 })
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Prime factorizations grouped into prime powers.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{FactorSieve, Sieve, SieveError};
+
+/// The prime factorization of a positive number, as each distinct prime with its exponent.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::new(100);
+/// let factorization = my_sieve.factorization(360).unwrap();
+///
+/// assert_eq!(factorization.factors(), &[(2, 3), (3, 2), (5, 1)]);
+/// assert_eq!(factorization.value(), 360);
+/// assert_eq!(factorization.to_string(), "2^3 · 3^2 · 5");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Factorization {
+    factors: Vec<(u64, u32)>,
+}
+
+impl Factorization {
+    // Group a sorted list of prime factors with multiplicity, like `factorize()` gives
+    pub(crate) fn from_sorted(primes: &[u64]) -> Factorization {
+        let mut factors: Vec<(u64, u32)> = Vec::new();
+        for &p in primes {
+            match factors.last_mut() {
+                Some((last, exponent)) if *last == p => *exponent += 1,
+                _ => factors.push((p, 1)),
+            }
+        }
+        Factorization { factors }
+    }
+
+    /// The distinct primes with their exponents, in ascending order. Empty for `1`.
+    pub fn factors(&self) -> &[(u64, u32)] {
+        &self.factors
+    }
+
+    /// The number that was factorized.
+    pub fn value(&self) -> u64 {
+        // Each number was factorized from a `u64`, so this can't overflow
+        self.factors
+            .iter()
+            .map(|&(p, exponent)| p.pow(exponent))
+            .product()
+    }
+
+    /// The product of the distinct primes, i.e. the largest squarefree divisor of the number.
+    pub fn radical(&self) -> u64 {
+        self.factors.iter().map(|&(p, _)| p).product()
+    }
+
+    /// Count the divisors of the number, including `1` and the number itself.
+    pub fn num_divisors(&self) -> u64 {
+        self.factors
+            .iter()
+            .map(|&(_, exponent)| u64::from(exponent) + 1)
+            .product()
+    }
+
+    /// Sum the divisors of the number, including `1` and the number itself.
+    ///
+    /// Returns a `u128`, since this can be several times larger than the number.
+    pub fn divisor_sum(&self) -> u128 {
+        self.factors
+            .iter()
+            .map(|&(p, exponent)| {
+                let p = u128::from(p);
+                (p.pow(exponent + 1) - 1) / (p - 1)
+            })
+            .product()
+    }
+}
+
+impl fmt::Display for Factorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.factors.is_empty() {
+            return write!(f, "1");
+        }
+        for (i, &(p, exponent)) in self.factors.iter().enumerate() {
+            if i > 0 {
+                write!(f, " · ")?;
+            }
+            write!(f, "{}", p)?;
+            if exponent > 1 {
+                write!(f, "^{}", exponent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sieve {
+    /// Find the prime factorization of `n` like `Sieve::factorize()`, but grouped into prime
+    /// powers.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    pub fn factorization(&self, n: u64) -> Result<Factorization, SieveError> {
+        Ok(Factorization::from_sorted(&self.factorize(n)?))
+    }
+}
+
+impl FactorSieve {
+    /// Find the prime factorization of `n` like `FactorSieve::factorize()`, but grouped into
+    /// prime powers.
+    ///
+    /// Returns the same errors as `FactorSieve::factorize()`.
+    pub fn factorization(&self, n: u64) -> Result<Factorization, SieveError> {
+        Ok(Factorization::from_sorted(&self.factorize(n)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn factorizations_round_trip() {
+        let sieve = Sieve::new(1000);
+        let factor_sieve = FactorSieve::new(10_000);
+        for n in 1..=10_000 {
+            let factorization = sieve.factorization(n).unwrap();
+            assert_eq!(factorization.value(), n);
+            assert_eq!(factor_sieve.factorization(n).unwrap(), factorization);
+            assert!(factorization
+                .factors()
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0));
+        }
+
+        let one = sieve.factorization(1).unwrap();
+        assert_eq!(one.factors(), &[]);
+        assert_eq!(
+            (one.value(), one.num_divisors(), one.divisor_sum()),
+            (1, 1, 1)
+        );
+        assert_eq!(one.to_string(), "1");
+        assert_eq!(sieve.factorization(997).unwrap().to_string(), "997");
+        assert_eq!(sieve.factorization(0), Err(SieveError::Zero));
+
+        let big = sieve.factorization(1 << 63).unwrap();
+        assert_eq!(big.to_string(), "2^63");
+        assert_eq!(big.value(), 1 << 63);
+        assert_eq!(big.divisor_sum(), (1 << 64) - 1);
+        assert_eq!(big.radical(), 2);
+        assert_eq!(one.radical(), 1);
+    }
+}
@@ -0,0 +1,199 @@
+//! A C API, with a header (generated by cbindgen) at `include/prime_sieve.h`.
+//!
+//! Build with the `ffi` feature as a `cdylib` or `staticlib`, e.g.
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//!
+//! Every function catches panics at the boundary and reports them as `SIEVE_STATUS_PANIC` (or a
+//! null pointer, for `sieve_new`) rather than unwinding into C.
+
+use std::panic::{self, UnwindSafe};
+use std::ptr;
+
+use crate::{Sieve, SieveError};
+
+/// The result of a call into the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SieveStatus {
+    /// The call succeeded and its output has been written.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// The sieve hasn't been populated.
+    NotFilled = 2,
+    /// The value asked about is outside the numbers the sieve covers.
+    OutOfBounds = 3,
+    /// There's no prime that satisfies the query within the sieve.
+    NotFound = 4,
+    /// Rust panicked; nothing has been written.
+    Panic = 5,
+    /// An argument was outside the domain of the query (e.g. `0`).
+    InvalidArgument = 6,
+    /// The answer is too large to fit in the output type.
+    Overflow = 7,
+}
+
+impl From<SieveError> for SieveStatus {
+    fn from(error: SieveError) -> SieveStatus {
+        match error {
+            SieveError::NotFilled => SieveStatus::NotFilled,
+            SieveError::OutOfBounds { .. } | SieveError::BelowRange { .. } => {
+                SieveStatus::OutOfBounds
+            }
+            SieveError::Zero | SieveError::NotPrime { .. } | SieveError::InvalidPrimes => {
+                SieveStatus::InvalidArgument
+            }
+            SieveError::Overflow => SieveStatus::Overflow,
+        }
+    }
+}
+
+fn guard<F: FnOnce() -> SieveStatus + UnwindSafe>(f: F) -> SieveStatus {
+    panic::catch_unwind(f).unwrap_or(SieveStatus::Panic)
+}
+
+/// Create and populate a prime sieve with the maximum value `max`.
+///
+/// Returns null if the sieve couldn't be created. The sieve must be released with `sieve_free`.
+#[no_mangle]
+pub extern "C" fn sieve_new(max: u64) -> *mut Sieve {
+    panic::catch_unwind(|| Box::into_raw(Box::new(Sieve::new(max)))).unwrap_or(ptr::null_mut())
+}
+
+/// Release a sieve created by `sieve_new`. Does nothing if `sieve` is null.
+///
+/// # Safety
+///
+/// `sieve` must be null or a pointer returned by `sieve_new` that hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn sieve_free(sieve: *mut Sieve) {
+    if !sieve.is_null() {
+        let _ = panic::catch_unwind(|| drop(Box::from_raw(sieve)));
+    }
+}
+
+/// Write the max value of `sieve` to `out`.
+///
+/// # Safety
+///
+/// `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn sieve_max(sieve: *const Sieve, out: *mut u64) -> SieveStatus {
+    guard(|| match (sieve.as_ref(), out.as_mut()) {
+        (Some(sieve), Some(out)) => {
+            *out = sieve.max();
+            SieveStatus::Ok
+        }
+        _ => SieveStatus::NullPointer,
+    })
+}
+
+/// Write whether `target` is prime to `out`.
+///
+/// # Safety
+///
+/// `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn sieve_lookup(
+    sieve: *const Sieve,
+    target: u64,
+    out: *mut bool,
+) -> SieveStatus {
+    guard(|| match (sieve.as_ref(), out.as_mut()) {
+        (Some(sieve), Some(out)) => match sieve.lookup(target) {
+            Ok(is_prime) => {
+                *out = is_prime;
+                SieveStatus::Ok
+            }
+            Err(error) => error.into(),
+        },
+        _ => SieveStatus::NullPointer,
+    })
+}
+
+/// Write the number of primes in `sieve` to `out`.
+///
+/// # Safety
+///
+/// `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn sieve_count_primes(sieve: *const Sieve, out: *mut u64) -> SieveStatus {
+    guard(|| match (sieve.as_ref(), out.as_mut()) {
+        (Some(sieve), Some(out)) => {
+            *out = sieve.count_primes();
+            SieveStatus::Ok
+        }
+        _ => SieveStatus::NullPointer,
+    })
+}
+
+/// Write the smallest prime strictly greater than `n` to `out`, or return `SIEVE_STATUS_NOT_FOUND`
+/// if there isn't one within the sieve.
+///
+/// # Safety
+///
+/// `sieve` must be null or a live pointer from `sieve_new`, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn sieve_next_prime(
+    sieve: *const Sieve,
+    n: u64,
+    out: *mut u64,
+) -> SieveStatus {
+    guard(|| match (sieve.as_ref(), out.as_mut()) {
+        (Some(sieve), Some(out)) => match sieve.next_prime(n) {
+            Some(prime) => {
+                *out = prime;
+                SieveStatus::Ok
+            }
+            None => SieveStatus::NotFound,
+        },
+        _ => SieveStatus::NullPointer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_through_c_api() {
+        unsafe {
+            let sieve = sieve_new(100);
+            assert!(!sieve.is_null());
+
+            let mut is_prime = false;
+            assert_eq!(sieve_lookup(sieve, 97, &mut is_prime), SieveStatus::Ok);
+            assert!(is_prime);
+            assert_eq!(
+                sieve_lookup(sieve, 101, &mut is_prime),
+                SieveStatus::OutOfBounds
+            );
+
+            let mut count = 0;
+            assert_eq!(sieve_count_primes(sieve, &mut count), SieveStatus::Ok);
+            assert_eq!(count, 25);
+            assert_eq!(
+                sieve_next_prime(sieve, 97, &mut count),
+                SieveStatus::NotFound
+            );
+
+            sieve_free(sieve);
+        }
+    }
+
+    #[test]
+    fn rejects_null_pointers() {
+        unsafe {
+            let mut max = 0;
+            assert_eq!(sieve_max(ptr::null(), &mut max), SieveStatus::NullPointer);
+            let sieve = sieve_new(10);
+            assert_eq!(sieve_max(sieve, ptr::null_mut()), SieveStatus::NullPointer);
+            sieve_free(sieve);
+            sieve_free(ptr::null_mut());
+        }
+    }
+}
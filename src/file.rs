@@ -0,0 +1,215 @@
+//! Saving sieves to (and loading them from) a compact binary file.
+//!
+//! The layout is an 8-byte magic string, a version byte, a kind byte (`0` for a full table, `1`
+//! for a segmented sieve), a flags byte (bit 0 set if the sieve is filled, bit 1 if it's part way
+//! through `Sieve::fill_chunk()`) and then the max as a little-endian `u64`. Part-filled tables
+//! have the number of chunks done next, as another `u64`. Full tables follow this with their
+//! packed words, also little-endian, and segmented sieves with their segment size - their base
+//! primes are cheap to recompute.
+//!
+//! Version 1 files are the same, but never part-filled.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::bits::{BitTable, WORD_BITS};
+use crate::{table_len, FillState, Sieve, Table, CHUNK_WORDS};
+
+const MAGIC: &[u8; 8] = b"PRMSIEVE";
+const VERSION: u8 = 2;
+const KIND_FULL: u8 = 0;
+const KIND_SEGMENTED: u8 = 1;
+const FLAG_FILLED: u8 = 1;
+const FLAG_PARTIAL: u8 = 2;
+
+impl Sieve {
+    /// Save this sieve to the file at `path`, overwriting it if it exists.
+    ///
+    /// ```no_run
+    /// let my_sieve = prime_sieve::Sieve::new(1_000_000_000);
+    /// my_sieve.write_to("primes.sieve").unwrap();
+    /// ```
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        let kind = match self.table {
+            Table::Full(_) => KIND_FULL,
+            Table::Segmented { .. } => KIND_SEGMENTED,
+        };
+        let next_chunk = match &self.fill_state {
+            Some(state) if state.next_chunk > 0 => Some(state.next_chunk as u64),
+            _ => None,
+        };
+        let mut flags = if self.filled { FLAG_FILLED } else { 0 };
+        if next_chunk.is_some() {
+            flags |= FLAG_PARTIAL;
+        }
+        writer.write_all(&[VERSION, kind, flags])?;
+        writer.write_all(&self.max.to_le_bytes())?;
+        if let Some(next_chunk) = next_chunk {
+            writer.write_all(&next_chunk.to_le_bytes())?;
+        }
+        match &self.table {
+            Table::Full(table) => {
+                for word in table.words() {
+                    writer.write_all(&word.to_le_bytes())?;
+                }
+            }
+            Table::Segmented { segment_size, .. } => {
+                writer.write_all(&segment_size.to_le_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Load a sieve previously saved with `Sieve::write_to()`.
+    ///
+    /// Returns an error with kind `io::ErrorKind::InvalidData` if the file isn't a valid sieve.
+    ///
+    /// ```no_run
+    /// let my_sieve = prime_sieve::Sieve::read_from("primes.sieve").unwrap();
+    /// ```
+    pub fn read_from<P: AsRef<Path>>(path: P) -> io::Result<Sieve> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a sieve file"));
+        }
+        let mut header = [0; 3];
+        reader.read_exact(&mut header)?;
+        let [version, kind, flags] = header;
+        if version != 1 && version != VERSION {
+            return Err(invalid_data("unsupported sieve file version"));
+        }
+        let max = read_u64(&mut reader)?;
+        let filled = flags & FLAG_FILLED != 0;
+        let next_chunk = if flags & FLAG_PARTIAL != 0 {
+            if version == 1 || filled || kind != KIND_FULL {
+                return Err(invalid_data("only unfilled full tables can be part-filled"));
+            }
+            Some(read_u64(&mut reader)?)
+        } else {
+            None
+        };
+
+        match kind {
+            KIND_FULL => {
+                let len = table_len(max);
+                // Not pre-allocated, so a corrupt max can't trigger a huge allocation up front
+                let mut words = Vec::new();
+                for _ in 0..len.div_ceil(WORD_BITS) {
+                    words.push(read_u64(&mut reader)?);
+                }
+                let fill_state = match next_chunk {
+                    Some(next_chunk) if next_chunk < words.len().div_ceil(CHUNK_WORDS) as u64 => {
+                        Some(FillState::new(max, next_chunk as usize))
+                    }
+                    Some(_) => return Err(invalid_data("sieve fill progress is past its end")),
+                    None => None,
+                };
+                let table = BitTable::from_words(words, len)
+                    .ok_or_else(|| invalid_data("sieve table has bits set past its end"))?;
+                Ok(Sieve {
+                    max,
+                    table: Table::Full(table),
+                    filled,
+                    fill_state,
+                })
+            }
+            KIND_SEGMENTED => {
+                let segment_size = read_u64(&mut reader)?;
+                if segment_size == 0 {
+                    return Err(invalid_data("segment size must be non-zero"));
+                }
+                let mut result = Sieve {
+                    max,
+                    table: Table::Segmented {
+                        segment_size,
+                        base_primes: Vec::new(),
+                    },
+                    filled: false,
+                    fill_state: None,
+                };
+                if filled {
+                    result.fill();
+                }
+                Ok(result)
+            }
+            _ => Err(invalid_data("unknown sieve kind")),
+        }
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn round_trips_through_file() {
+        let path = env::temp_dir().join(format!("prime_sieve_test_{}.sieve", std::process::id()));
+        for original in [
+            Sieve::new(100_000),
+            Sieve::unfilled(77),
+            Sieve::segmented(10_000, 128),
+        ] {
+            original.write_to(&path).unwrap();
+            let restored = Sieve::read_from(&path).unwrap();
+            assert_eq!(restored.max(), original.max());
+            assert_eq!(restored.first_difference(&original), None);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn round_trips_part_filled() {
+        let path = env::temp_dir().join(format!("prime_sieve_part_{}.sieve", std::process::id()));
+        let mut original = Sieve::unfilled(10_000_000);
+        original.fill_chunk(4);
+        original.write_to(&path).unwrap();
+        let mut restored = Sieve::read_from(&path).unwrap();
+        assert_eq!(restored.sieved_up_to(), original.sieved_up_to());
+        // Only the rest of the chunks are left to do
+        let mut steps = 0;
+        while restored.fill_chunk(1) {
+            steps += 1;
+        }
+        assert_eq!(steps, 6);
+        assert_eq!(restored, Sieve::new(10_000_000));
+
+        // Progress beyond the table can't be right
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[19..27].copy_from_slice(&11u64.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(Sieve::read_from(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let path = env::temp_dir().join(format!("prime_sieve_bad_{}.sieve", std::process::id()));
+        Sieve::new(100_000).write_to(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(Sieve::read_from(&path).is_err());
+        fs::write(&path, b"not a sieve at all").unwrap();
+        assert_eq!(
+            Sieve::read_from(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+//! Filling sieves from async code without blocking the executor.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::Sieve;
+
+// Fills a chunk per poll, asking to be polled again straight away until it's done
+struct FillAsync<'a, F> {
+    sieve: &'a mut Sieve,
+    progress: F,
+}
+
+// Nothing is ever pinned in place, so moving a `FillAsync` is always fine
+impl<F> Unpin for FillAsync<'_, F> {}
+
+impl<F: FnMut(u64, u64)> Future for FillAsync<'_, F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.sieve.filled {
+            return Poll::Ready(());
+        }
+        if this.sieve.fill_chunk(1) {
+            (this.progress)(this.sieve.sieved_up_to(), this.sieve.max);
+            context.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            (this.progress)(this.sieve.max, this.sieve.max);
+            Poll::Ready(())
+        }
+    }
+}
+
+impl Sieve {
+    /// Populate an unfilled sieve like `Sieve::fill()`, as a future that sieves a chunk of about a
+    /// million numbers each time it's polled and yields to the executor in between.
+    ///
+    /// This works with any executor, since it only needs waking to carry on. Has no effect on
+    /// already-filled sieves.
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::task::{Context, Waker};
+    ///
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(10_000_000);
+    /// {
+    ///     // A very simple executor - normally you'd just `.await` it
+    ///     let mut fill = std::pin::pin!(my_sieve.fill_async());
+    ///     let mut context = Context::from_waker(Waker::noop());
+    ///     while fill.as_mut().poll(&mut context).is_pending() {}
+    /// }
+    ///
+    /// assert_eq!(my_sieve.count_primes(), 664_579);
+    /// ```
+    pub fn fill_async(&mut self) -> impl Future<Output = ()> + '_ {
+        self.fill_async_with_progress(|_, _| {})
+    }
+
+    /// Like `Sieve::fill_async()`, but calling `progress(done, total)` after each chunk the same
+    /// way as `Sieve::fill_with_progress()`.
+    pub fn fill_async_with_progress<'a, F: FnMut(u64, u64) + 'a>(
+        &'a mut self,
+        progress: F,
+    ) -> impl Future<Output = ()> + 'a {
+        FillAsync {
+            sieve: self,
+            progress,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::task::Waker;
+
+    // Poll `future` to completion, returning how many times that took
+    fn run<T: Future>(future: T) -> usize {
+        let mut future = core::pin::pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        let mut polls = 1;
+        while future.as_mut().poll(&mut context).is_pending() {
+            polls += 1;
+        }
+        polls
+    }
+
+    #[test]
+    fn async_fill_matches_fill() {
+        for max in [0, 1, 1000, 5_000_000] {
+            let mut sieve = Sieve::unfilled(max);
+            run(sieve.fill_async());
+            assert_eq!(sieve, Sieve::new(max), "max {}", max);
+        }
+
+        let mut sieve = Sieve::unfilled(5_000_000);
+        let mut reports = Vec::new();
+        let polls = run(sieve.fill_async_with_progress(|done, _| reports.push(done)));
+        assert_eq!(polls, reports.len());
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reports.last(), Some(&5_000_000));
+
+        // Nothing to do the second time around
+        assert_eq!(run(sieve.fill_async()), 1);
+        let mut segmented = Sieve::unfilled_segmented(1_000_000, 100);
+        assert_eq!(run(segmented.fill_async()), 1);
+        assert_eq!(segmented.count_primes(), 78_498);
+    }
+}
@@ -0,0 +1,58 @@
+//! A shared, automatically growing sieve for quick one-off primality checks.
+
+use std::sync::{OnceLock, RwLock};
+
+use crate::{primality, Sieve};
+
+/// The max the shared sieve starts out with
+const INITIAL_MAX: u64 = 1 << 16;
+
+/// The shared sieve never grows past this - anything bigger is tested with Baillie-PSW instead
+const GROWTH_LIMIT: u64 = 1 << 28;
+
+static SHARED: OnceLock<RwLock<Sieve>> = OnceLock::new();
+
+/// Determine whether `n` is prime, using a sieve shared by the whole program.
+///
+/// The sieve is created on first use and grows (at least doubling each time) to cover larger
+/// queries, up to a fixed limit past which a Baillie-PSW test is used instead.
+///
+/// ```
+/// assert_eq!(prime_sieve::is_prime(97), true);
+/// assert_eq!(prime_sieve::is_prime(1_000_000), false);
+/// assert_eq!(prime_sieve::is_prime(18_446_744_073_709_551_557), true);
+/// ```
+pub fn is_prime(n: u64) -> bool {
+    if n > GROWTH_LIMIT {
+        return primality::baillie_psw(n);
+    }
+    let shared = SHARED.get_or_init(|| RwLock::new(Sieve::new(INITIAL_MAX)));
+    {
+        // The sieve is never left half-updated, so a poisoned lock is still safe to read
+        let sieve = shared.read().unwrap_or_else(|e| e.into_inner());
+        if let Ok(answer) = sieve.lookup(n) {
+            return answer;
+        }
+    }
+    let mut sieve = shared.write().unwrap_or_else(|e| e.into_inner());
+    // Another thread may have grown it while we waited for the lock
+    if n > sieve.max() {
+        let new_max = n.max(sieve.max().saturating_mul(2)).min(GROWTH_LIMIT);
+        sieve.extend_to(new_max);
+    }
+    sieve.is_prime_unchecked(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_to_answer() {
+        let expected = Sieve::new(300_000);
+        for n in (0..300_000).step_by(997) {
+            assert_eq!(is_prime(n), expected.lookup(n).unwrap());
+        }
+        assert!(SHARED.get().unwrap().read().unwrap().max() >= 299_000);
+    }
+}
@@ -0,0 +1,85 @@
+//! Sieves that aren't populated until they're first used.
+
+use core::ops::Deref;
+use std::sync::OnceLock;
+
+use crate::Sieve;
+
+/// A sieve that fills itself the first time it's queried, made with `Sieve::lazy()`.
+///
+/// Derefs to a filled `Sieve`, so every query works as normal. Only the first one pays for
+/// sieving, and if several threads get there at once just one of them does it.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::lazy(1_000_000);
+/// assert!(!my_sieve.is_filled());
+///
+/// assert_eq!(my_sieve.lookup(999_983), Ok(true));
+/// assert!(my_sieve.is_filled());
+/// ```
+#[derive(Debug)]
+pub struct LazySieve {
+    max: u64,
+    sieve: OnceLock<Sieve>,
+}
+
+impl LazySieve {
+    /// Get the max value of this sieve, without filling it.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Whether the sieve has been filled yet.
+    pub fn is_filled(&self) -> bool {
+        self.sieve.get().is_some()
+    }
+
+    /// Fill the sieve if it isn't already, and take it out.
+    pub fn into_inner(self) -> Sieve {
+        let max = self.max;
+        self.sieve.into_inner().unwrap_or_else(|| Sieve::new(max))
+    }
+}
+
+impl Deref for LazySieve {
+    type Target = Sieve;
+
+    fn deref(&self) -> &Sieve {
+        self.sieve.get_or_init(|| Sieve::new(self.max))
+    }
+}
+
+impl Sieve {
+    /// Create a sieve with the maximum value `max` that's only populated when it's first queried.
+    ///
+    /// This is as cheap as `Sieve::unfilled()`, since nothing is allocated until then either.
+    pub fn lazy(max: u64) -> LazySieve {
+        LazySieve {
+            max,
+            sieve: OnceLock::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_on_first_use() {
+        let sieve = Sieve::lazy(10_000);
+        assert_eq!(sieve.max(), 10_000);
+        assert!(!sieve.is_filled());
+        assert_eq!(sieve.count_primes(), 1229);
+        assert!(sieve.is_filled());
+        assert_eq!(sieve.into_inner(), Sieve::new(10_000));
+        assert_eq!(Sieve::lazy(100).into_inner(), Sieve::new(100));
+
+        let shared = Sieve::lazy(1_000_000);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(shared.count_primes(), 78_498));
+            }
+        });
+    }
+}
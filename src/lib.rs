@@ -1,8 +1,134 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod atkin;
+#[cfg(feature = "bigint")]
+mod bigint;
+mod bits;
+mod builder;
+#[cfg(feature = "std")]
+mod cache;
+mod certificate;
+mod composites;
+mod const_sieve;
+mod digits;
+mod error;
+#[cfg(feature = "std")]
+pub mod estimates;
+mod factor;
+mod factorization;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "std")]
+mod global;
+#[cfg(feature = "std")]
+mod lazy;
+mod linear;
+#[cfg(feature = "mmap")]
+mod mapped;
+pub mod modular;
+pub mod multiplicative;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "crossbeam")]
+mod pipeline;
+mod presieve;
+pub mod primality;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "rand")]
+mod random;
+mod range;
+mod segmented;
+mod sequences;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod sieve32;
+mod slice;
+mod stream;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod wheel;
+
+pub use builder::{Algorithm, BuildError, SieveBuilder};
+#[cfg(feature = "std")]
+pub use cache::SieveCache;
+pub use certificate::PrattCertificate;
+pub use const_sieve::const_sieve;
+pub use error::SieveError;
+pub use factor::FactorSieve;
+pub use factorization::Factorization;
+#[cfg(feature = "std")]
+pub use global::is_prime;
+#[cfg(feature = "std")]
+pub use lazy::LazySieve;
+#[cfg(feature = "crossbeam")]
+pub use pipeline::PipelinedPrimes;
+pub use range::RangeSieve;
+pub use sieve32::Sieve32;
+pub use slice::SieveSlice;
+pub use stream::PrimeStream;
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{Bound, Index, RangeBounds};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use bits::BitTable;
+use segmented::SegmentedPrimes;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_support::RawSieve"))]
 pub struct Sieve {
     max: u64,
-    sieve_table: Vec<bool>,
+    table: Table,
     filled: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fill_state: Option<FillState>,
+}
+
+// How far through filling a full table `Sieve::fill_chunk()` has got
+#[derive(Debug)]
+struct FillState {
+    base_primes: Vec<u64>,
+    next_chunk: usize,
+}
+
+impl FillState {
+    // Pick up filling a full table with the given max from the start of chunk `next_chunk`
+    fn new(max: u64, next_chunk: usize) -> FillState {
+        // `fill` is built on this, so stop recursing once only presieved primes are needed
+        let bound = sqrt_floor(max);
+        let base_primes = if bound > presieve::LARGEST_PRESIEVED {
+            Sieve::new(bound).primes().collect()
+        } else {
+            Vec::new()
+        };
+        FillState {
+            base_primes,
+            next_chunk,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Table {
+    /// A bit for every number up to `max` that's coprime to 30 (see the `wheel` module)
+    Full(BitTable),
+    /// Just the primes up to `√max`, everything else is sieved a segment at a time on demand
+    Segmented {
+        segment_size: u64,
+        base_primes: Vec<u64>,
+    },
 }
 
 impl Sieve {
@@ -16,8 +142,9 @@ impl Sieve {
     pub fn unfilled(max: u64) -> Sieve {
         Sieve {
             max,
-            sieve_table: (0..=max).map(|_| true).collect(),
+            table: Table::Full(BitTable::ones(table_len(max))),
             filled: false,
+            fill_state: None,
         }
     }
 
@@ -31,6 +158,132 @@ impl Sieve {
         result
     }
 
+    /// Create and populate a prime sieve with the maximum value `max`, using the Sieve of Atkin
+    /// rather than the Sieve of Eratosthenes.
+    ///
+    /// The result is identical to `Sieve::new(max)`, which makes this useful both for performance
+    /// experiments and as a cross-check of correctness (see `Sieve::first_difference()`).
+    ///
+    /// ```
+    /// use prime_sieve::Sieve;
+    ///
+    /// let atkin = Sieve::new_atkin(10_000);
+    /// assert_eq!(atkin.first_difference(&Sieve::new(10_000)), None);
+    /// ```
+    pub fn new_atkin(max: u64) -> Sieve {
+        Sieve {
+            max,
+            table: Table::Full(atkin::sieve(max)),
+            filled: true,
+            fill_state: None,
+        }
+    }
+
+    /// Create a filled prime sieve with the maximum value `max` from a list of every prime up to
+    /// `max` in ascending order (e.g. one loaded from a file), rather than sieving again.
+    ///
+    /// Every entry is checked with `primality::miller_rabin()`. Missing primes are found by
+    /// comparing the list's length with a fresh count for `max` up to 2^24, where sieving again is
+    /// cheap; above that (with the `std` feature) the list only has to be long enough that
+    /// `estimates::nth_prime_upper_bound()` doesn't put another prime below `max`, which catches
+    /// big gaps but not a prime or two going missing.
+    ///
+    /// Returns `Err(SieveError::OutOfBounds)` if the list contains a value above `max`,
+    /// `Err(SieveError::NotPrime)` if it contains a value that isn't prime, or
+    /// `Err(SieveError::InvalidPrimes)` if it's out of order or is missing primes.
+    ///
+    /// ```
+    /// use prime_sieve::{Sieve, SieveError};
+    ///
+    /// let my_sieve = Sieve::from_primes(20, &[2, 3, 5, 7, 11, 13, 17, 19]).unwrap();
+    /// assert_eq!(my_sieve.lookup(17), Ok(true));
+    /// assert_eq!(my_sieve.lookup(18), Ok(false));
+    ///
+    /// assert_eq!(Sieve::from_primes(20, &[2, 5, 7]).unwrap_err(), SieveError::InvalidPrimes);
+    /// assert_eq!(
+    ///     Sieve::from_primes(100, &[2, 3, 5, 49]).unwrap_err(),
+    ///     SieveError::NotPrime { value: 49 }
+    /// );
+    /// ```
+    pub fn from_primes(max: u64, primes: &[u64]) -> Result<Sieve, SieveError> {
+        let mut table = BitTable::zeros(table_len(max));
+        let mut previous = 0;
+        for &p in primes {
+            if p > max {
+                return Err(SieveError::OutOfBounds { value: p, max });
+            }
+            if p <= previous {
+                return Err(SieveError::InvalidPrimes);
+            }
+            previous = p;
+            if !primality::miller_rabin(p) {
+                return Err(SieveError::NotPrime { value: p });
+            }
+            if wheel::is_candidate(p) {
+                table.toggle(wheel::index(p) as usize);
+            }
+        }
+        // The wheel primes aren't in the table, so make sure they're where they belong
+        let small = primes.iter().take_while(|&&p| p < 7);
+        if !small.eq(wheel::WHEEL_PRIMES.iter().filter(|&&p| p <= max)) {
+            return Err(SieveError::InvalidPrimes);
+        }
+        let count = primes.len() as u64;
+        let missing = if max <= FRESH_COUNT_LIMIT {
+            count != Sieve::new(max).count_primes()
+        } else {
+            #[cfg(feature = "std")]
+            let missing = estimates::nth_prime_upper_bound(count + 1) <= max;
+            #[cfg(not(feature = "std"))]
+            let missing = false;
+            missing
+        };
+        if missing {
+            return Err(SieveError::InvalidPrimes);
+        }
+        Ok(Sieve {
+            max,
+            table: Table::Full(table),
+            filled: true,
+            fill_state: None,
+        })
+    }
+
+    /// Create and populate a segmented prime sieve with the maximum value `max`.
+    ///
+    /// Only the primes up to `√max` are stored - everything else is sieved `segment_size` numbers
+    /// at a time as it's needed, so memory use stays bounded even for huge values of `max`.
+    ///
+    /// Panics if `segment_size` is `0`. Segment sizes too large to address on this platform are
+    /// clamped.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::segmented(10_000_000_000, 1 << 16);
+    ///
+    /// assert_eq!(my_sieve.lookup(9_999_999_967).unwrap(), true);
+    /// assert_eq!(my_sieve.next_prime(9_999_999_967), None);
+    /// ```
+    pub fn segmented(max: u64, segment_size: u64) -> Sieve {
+        let mut result = Sieve::unfilled_segmented(max, segment_size);
+        result.fill();
+        result
+    }
+
+    // Create a segmented sieve like `Sieve::segmented()`, but don't populate it
+    fn unfilled_segmented(max: u64, segment_size: u64) -> Sieve {
+        assert!(segment_size > 0, "Segment size must be non-zero");
+        let segment_size = segment_size.min(usize::MAX as u64);
+        Sieve {
+            max,
+            table: Table::Segmented {
+                segment_size,
+                base_primes: Vec::new(),
+            },
+            filled: false,
+            fill_state: None,
+        }
+    }
+
     /// Get the max value of this sieve
     ///
     /// ```
@@ -38,18 +291,393 @@ impl Sieve {
     /// assert_eq!(my_sieve.max(), 10);
     /// ```
     pub fn max(&self) -> u64 {
-        return self.max;
+        self.max
+    }
+
+    /// Get the number of bytes this sieve's table takes up on the heap.
+    ///
+    /// Full tables use about a byte for every 30 numbers (or nothing, if they're memory-mapped),
+    /// segmented sieves just store their base primes (the segment buffers used while iterating
+    /// aren't counted).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1_000_000);
+    /// assert_eq!(my_sieve.heap_size(), 33_336);
+    /// ```
+    pub fn heap_size(&self) -> usize {
+        match &self.table {
+            Table::Full(table) => table.heap_size(),
+            Table::Segmented { base_primes, .. } => base_primes.len() * size_of::<u64>(),
+        }
+    }
+
+    /// Iterate over every prime in this sieve in ascending order, without allocating.
+    ///
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(20);
+    ///
+    /// let primes: Vec<u64> = my_sieve.primes().collect();
+    /// assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19]);
+    /// ```
+    pub fn primes(&self) -> Primes<'_> {
+        self.primes_from(0)
+    }
+
+    /// Collect every prime in this sieve into a `Vec`, in ascending order.
+    ///
+    /// The `Vec` is allocated up front with room for an upper bound on the number of primes, so it
+    /// never has to grow. Returns an empty `Vec` if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(20);
+    ///
+    /// assert_eq!(my_sieve.primes_vec(), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+    /// ```
+    pub fn primes_vec(&self) -> Vec<u64> {
+        if !self.filled {
+            return Vec::new();
+        }
+        let capacity = usize::try_from(prime_count_upper_bound(self.max)).unwrap_or(usize::MAX);
+        let mut primes = Vec::with_capacity(capacity);
+        primes.extend(self.primes());
+        primes
+    }
+
+    /// Iterate over the primes within `range` in ascending order (or descending, with `.rev()`),
+    /// without walking the rest of the table. Any part of the range beyond `sieve.max()` is
+    /// ignored.
+    ///
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(2000);
+    ///
+    /// let primes: Vec<u64> = my_sieve.primes_in_range(1000..1050).collect();
+    /// assert_eq!(primes, vec![1009, 1013, 1019, 1021, 1031, 1033, 1039, 1049]);
+    /// assert_eq!(my_sieve.primes_in_range(1990..).count(), 3);
+    /// assert_eq!(my_sieve.primes_in_range(..1000).next_back(), Some(997));
+    /// ```
+    pub fn primes_in_range<R: RangeBounds<u64>>(&self, range: R) -> Primes<'_> {
+        let (start, end) = inclusive_bounds(&range);
+        self.primes_between(start, end)
+    }
+
+    // Iterate over the primes `>= low`, or nothing at all if the sieve is unfilled
+    fn primes_from(&self, low: u64) -> Primes<'_> {
+        self.primes_between(low, self.max)
+    }
+
+    // Iterate over the primes in `low..=high`, or nothing at all if the sieve is unfilled
+    fn primes_between(&self, low: u64, high: u64) -> Primes<'_> {
+        let high = high.min(self.max);
+        if !self.filled || low > high {
+            return Primes(PrimesInner::Done);
+        }
+        Primes(match &self.table {
+            Table::Full(table) => PrimesInner::Full {
+                table,
+                max: high,
+                next: low,
+            },
+            Table::Segmented {
+                segment_size,
+                base_primes,
+            } => {
+                PrimesInner::Segmented(SegmentedPrimes::new(base_primes, high, *segment_size, low))
+            }
+        })
+    }
+
+    /// Get the `n`th prime (counting from 1, so `nth_prime(1) == Some(2)`).
+    ///
+    /// Returns `None` if `n == 0`, the sieve is unfilled, or it doesn't contain `n` primes.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.nth_prime(1), Some(2));
+    /// assert_eq!(my_sieve.nth_prime(25), Some(97));
+    /// assert_eq!(my_sieve.nth_prime(26), None);
+    /// ```
+    pub fn nth_prime(&self, n: usize) -> Option<u64> {
+        self.primes().nth(n.checked_sub(1)?)
+    }
+
+    /// Count the primes in this sieve, i.e. π(max).
+    ///
+    /// Returns `0` if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.count_primes(), 25);
+    /// ```
+    pub fn count_primes(&self) -> u64 {
+        self.primes().count() as u64
+    }
+
+    /// Count the primes strictly less than `n`.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, or
+    /// `Err(SieveError::OutOfBounds)` if `n > sieve.max() + 1`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.count_primes_below(10).unwrap(), 4);
+    /// assert_eq!(my_sieve.count_primes_below(11).unwrap(), 4);
+    /// assert_eq!(my_sieve.count_primes_below(12).unwrap(), 5);
+    /// ```
+    pub fn count_primes_below(&self, n: u64) -> Result<u64, SieveError> {
+        if n == 0 {
+            return self.lookup(0).map(|_| 0);
+        }
+        self.lookup(n - 1)?;
+        Ok(self.primes().take_while(|&p| p < n).count() as u64)
+    }
+
+    /// Add up all the primes in this sieve.
+    ///
+    /// Returns `0` if the sieve is unfilled. The sum is a `u128` since it overflows a `u64` once
+    /// `max` passes about 3 × 10¹⁰.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.sum_primes(), 1060);
+    /// ```
+    pub fn sum_primes(&self) -> u128 {
+        self.primes().map(u128::from).sum()
+    }
+
+    /// Add up the primes strictly less than `n`.
+    ///
+    /// Returns the same errors as `Sieve::count_primes_below()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.sum_primes_below(10).unwrap(), 17);
+    /// assert_eq!(my_sieve.sum_primes_below(12).unwrap(), 28);
+    /// assert!(my_sieve.sum_primes_below(1000).is_err());
+    /// ```
+    pub fn sum_primes_below(&self, n: u64) -> Result<u128, SieveError> {
+        if n == 0 {
+            return self.lookup(0).map(|_| 0);
+        }
+        self.lookup(n - 1)?;
+        Ok(self.primes().take_while(|&p| p < n).map(u128::from).sum())
+    }
+
+    /// Multiply together all the primes `<= n`, i.e. the primorial `n#`. `primorial(0)` and
+    /// `primorial(1)` are both `1`.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, `Err(SieveError::OutOfBounds)`
+    /// if `n > sieve.max()`, or `Err(SieveError::Overflow)` if the product doesn't fit in a `u128`
+    /// (which happens for every `n >= 103`).
+    ///
+    /// ```
+    /// use prime_sieve::{Sieve, SieveError};
+    ///
+    /// let my_sieve = Sieve::new(200);
+    ///
+    /// assert_eq!(my_sieve.primorial(10), Ok(210));
+    /// assert_eq!(my_sieve.primorial(103), Err(SieveError::Overflow));
+    /// ```
+    pub fn primorial(&self, n: u64) -> Result<u128, SieveError> {
+        self.lookup(n)?;
+        self.primes()
+            .take_while(|&p| p <= n)
+            .try_fold(1u128, |product, p| product.checked_mul(u128::from(p)))
+            .ok_or(SieveError::Overflow)
+    }
+
+    /// Find the smallest prime strictly greater than `n`.
+    ///
+    /// Returns `None` if the sieve is unfilled or there's no such prime `<= sieve.max()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.next_prime(7), Some(11));
+    /// assert_eq!(my_sieve.next_prime(97), None);
+    /// ```
+    pub fn next_prime(&self, n: u64) -> Option<u64> {
+        if !self.filled || n >= self.max {
+            return None;
+        }
+        self.primes_from(n + 1).next()
+    }
+
+    /// Find the largest prime strictly less than `n`.
+    ///
+    /// Returns `None` if the sieve is unfilled, there is no smaller prime, or `n > sieve.max() + 1`
+    /// (since the sieve can't tell whether there are primes between its max and `n`).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.prev_prime(11), Some(7));
+    /// assert_eq!(my_sieve.prev_prime(2), None);
+    /// assert_eq!(my_sieve.prev_prime(1000), None);
+    /// ```
+    pub fn prev_prime(&self, n: u64) -> Option<u64> {
+        if !self.filled || n == 0 || n - 1 > self.max {
+            return None;
+        }
+        self.primes_between(0, n - 1).next_back()
+    }
+
+    /// Find the smallest number that this sieve and `other` disagree on the primality of, looking
+    /// only at the numbers both of them cover.
+    ///
+    /// Returns `None` if they agree everywhere. Two unfilled sieves always agree, but an unfilled
+    /// sieve disagrees with a filled one at `0`.
+    ///
+    /// ```
+    /// use prime_sieve::Sieve;
+    ///
+    /// let small = Sieve::new(100);
+    /// let segmented = Sieve::segmented(1000, 64);
+    ///
+    /// assert_eq!(small.first_difference(&segmented), None);
+    /// assert_eq!(small.first_difference(&Sieve::unfilled(10)), Some(0));
+    /// ```
+    pub fn first_difference(&self, other: &Sieve) -> Option<u64> {
+        if self.filled != other.filled {
+            return Some(0);
+        }
+        if !self.filled {
+            return None;
+        }
+        let max = self.max.min(other.max);
+        let mut ours = self.primes().take_while(|&p| p <= max);
+        let mut theirs = other.primes().take_while(|&p| p <= max);
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return None,
+                (Some(a), Some(b)) if a == b => continue,
+                (Some(a), Some(b)) => return Some(a.min(b)),
+                (Some(p), None) | (None, Some(p)) => return Some(p),
+            }
+        }
+    }
+
+    /// Raise the max value of this sieve to `new_max`, only sieving the newly-covered numbers.
+    ///
+    /// Has no effect if `new_max <= sieve.max()`. An unfilled sieve stays unfilled.
+    ///
+    /// ```
+    /// let mut my_sieve = prime_sieve::Sieve::new(100);
+    /// my_sieve.extend_to(1000);
+    ///
+    /// assert_eq!(my_sieve.max(), 1000);
+    /// assert_eq!(my_sieve.lookup(997).unwrap(), true);
+    /// ```
+    pub fn extend_to(&mut self, new_max: u64) {
+        if new_max <= self.max {
+            return;
+        }
+        let old_max = self.max;
+        let bound = sqrt_floor(new_max);
+        let base_primes: Vec<u64> = if !self.filled {
+            Vec::new()
+        } else if bound <= old_max {
+            self.primes().take_while(|&p| p <= bound).collect()
+        } else {
+            Sieve::new(bound).primes().collect()
+        };
+
+        self.max = new_max;
+        // Any fill in progress was using base primes for the old max
+        self.fill_state = None;
+        match &mut self.table {
+            Table::Full(table) => {
+                table.grow(table_len(new_max));
+                if self.filled {
+                    let first_word =
+                        wheel::index_at_or_after(old_max + 1) as usize / bits::WORD_BITS;
+                    let words = table.words_mut();
+                    presieve::presieve(&mut words[first_word..], first_word, new_max);
+                    mark_composites(words, 0, &base_primes, old_max + 1, new_max);
+                }
+            }
+            Table::Segmented {
+                base_primes: old_base_primes,
+                ..
+            } => {
+                if self.filled {
+                    *old_base_primes = base_primes;
+                }
+            }
+        }
     }
 
-    // Warning: doesn't check if the target is out of bounds
-    fn process_ahead(&mut self, target: u64) {
-        if !self.sieve_table[target as usize] {
+    /// Lower the max value of this sieve to `new_max`, freeing the memory used for anything above
+    /// it.
+    ///
+    /// Has no effect if `new_max >= sieve.max()`. Segmented sieves drop the base primes they no
+    /// longer need. A fill in progress carries on from where it was.
+    ///
+    /// ```
+    /// let mut my_sieve = prime_sieve::Sieve::new(1_000_000);
+    /// my_sieve.truncate(1000);
+    ///
+    /// assert_eq!(my_sieve.max(), 1000);
+    /// assert_eq!(my_sieve.count_primes(), 168);
+    /// assert!(my_sieve.heap_size() < 100);
+    /// ```
+    pub fn truncate(&mut self, new_max: u64) {
+        if new_max >= self.max {
             return;
         }
-        let mut cur_target = 2 * target;
-        while cur_target <= self.max {
-            self.sieve_table[cur_target as usize] = false;
-            cur_target += target;
+        self.max = new_max;
+        match &mut self.table {
+            Table::Full(table) => table.shrink(table_len(new_max)),
+            Table::Segmented { base_primes, .. } => {
+                let bound = sqrt_floor(new_max);
+                base_primes.retain(|&p| p <= bound);
+                base_primes.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Combine two sieves into one covering the larger of their maxima.
+    ///
+    /// Every sieve starts at zero, so a filled sieve already knows everything a smaller one does
+    /// and is returned as it is. If only the smaller sieve is filled, it's extended with
+    /// `Sieve::extend_to()`, which only sieves the numbers it didn't already cover. Merging two
+    /// unfilled sieves gives an unfilled one.
+    ///
+    /// ```
+    /// use prime_sieve::Sieve;
+    ///
+    /// let merged = Sieve::new(1000).merge(Sieve::unfilled(10_000));
+    /// assert_eq!(merged.max(), 10_000);
+    /// assert_eq!(merged, Sieve::new(10_000));
+    /// ```
+    pub fn merge(self, other: Sieve) -> Sieve {
+        let (larger, mut smaller) = if self.max >= other.max {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        if larger.filled || !smaller.filled {
+            return larger;
+        }
+        smaller.extend_to(larger.max);
+        smaller
+    }
+
+    // Warning: doesn't check if the sieve is filled or if the target is out of bounds
+    fn is_prime_unchecked(&self, target: u64) -> bool {
+        match &self.table {
+            Table::Full(table) => {
+                if wheel::is_candidate(target) {
+                    table.get(wheel::index(target) as usize)
+                } else {
+                    wheel::WHEEL_PRIMES.contains(&target)
+                }
+            }
+            Table::Segmented { base_primes, .. } => segmented::is_prime(base_primes, target),
         }
     }
 
@@ -71,66 +699,736 @@ impl Sieve {
         if self.filled {
             return;
         }
-        self.sieve_table[0] = false;
-        self.sieve_table[1] = false;
-        for i in 2..=((self.max as f64).sqrt() as u64) {
-            self.process_ahead(i);
+        match &mut self.table {
+            // Sieving a cache-sized chunk at a time, rather than striding each prime across the
+            // whole table, keeps the words being marked in L1
+            Table::Full(_) => while self.fill_chunk(usize::MAX) {},
+            Table::Segmented { base_primes, .. } => {
+                *base_primes = Sieve::new(sqrt_floor(self.max)).primes().collect();
+                self.filled = true;
+                self.fill_state = None;
+            }
         }
-        self.filled = true;
     }
 
-    /// Determine whether a number within the prime sieve's limits is trule prime or not
+    /// Populate an unfilled sieve like `Sieve::fill()`, calling `progress(done, total)` as it goes
+    /// to report that every number up to `done` out of `total` (which is `sieve.max()`) has been
+    /// sieved. The last call always has `done == total`.
     ///
-    /// Returns `Err()` if sieve is unpopulated or if `target > sieve.max()`.
-    pub fn lookup(&self, target: u64) -> Result<bool, String> {
-        if !self.filled {
-            Err(String::from("Sieve not populated!"))
-        } else if target > self.max {
-            Err(format!(
-                "{} is out of this sieve's bounds (max {})",
-                target, self.max
-            ))
-        } else {
-            Ok(self.sieve_table[target as usize])
-        }
+    /// There's a call for each chunk of about a million numbers sieved (see `Sieve::fill_chunk()`),
+    /// use `Sieve::fill_with_progress_every()` for fewer.
+    ///
+    /// Has no effect on already-filled sieves.
+    ///
+    /// ```
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(10_000_000);
+    ///
+    /// let mut reports = Vec::new();
+    /// my_sieve.fill_with_progress(|done, total| reports.push((done, total)));
+    ///
+    /// assert!(reports.len() > 1);
+    /// assert_eq!(reports.last(), Some(&(10_000_000, 10_000_000)));
+    /// assert_eq!(my_sieve.count_primes(), 664_579);
+    /// ```
+    pub fn fill_with_progress<F: FnMut(u64, u64)>(&mut self, progress: F) {
+        self.fill_with_progress_every(1, progress);
     }
 
-    /// Takes a vector of `u64`s and removes all the non-prime ones.
+    /// Like `Sieve::fill_with_progress()`, but only calling `progress` after every `steps` chunks
+    /// (and at the end), for when reporting is expensive.
     ///
-    /// Will return `Err()` if one of `target`'s elements is outside the bounds of this sieve
+    /// Panics if `steps` is `0`.
     ///
     /// ```
-    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(10_000_000);
     ///
-    /// let filtered = my_sieve.filter(vec![1,2,3,4]).unwrap();
-    /// assert_eq!(filtered, vec![2,3]);
+    /// let mut reports = 0;
+    /// my_sieve.fill_with_progress_every(4, |_, _| reports += 1);
+    ///
+    /// // 11 chunks, reported after the 4th, 8th and 11th
+    /// assert_eq!(reports, 3);
     /// ```
-    pub fn filter(&self, target: Vec<u64>) -> Result<Vec<u64>, String> {
-        let mut result: Vec<u64> = Vec::new();
-        for i in target.into_iter() {
-            if self.lookup(i)? {
-                result.push(i);
-            }
+    pub fn fill_with_progress_every<F: FnMut(u64, u64)>(&mut self, steps: usize, mut progress: F) {
+        assert!(steps > 0, "Steps between progress reports must be non-zero");
+        if self.filled {
+            return;
         }
-        Ok(result)
+        while self.fill_chunk(steps) {
+            progress(self.sieved_up_to(), self.max);
+        }
+        progress(self.max, self.max);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::assert;
-    // use test::Bencher;
 
-    #[test]
-    fn marks_correctly_low() {
-        let test_case = Sieve::new(10);
-        assert!(test_case.lookup(5).unwrap());
+    /// Populate an unfilled sieve like `Sieve::fill()`, but stop early if `cancel` is set (from
+    /// another thread, say). Returns whether the sieve ended up filled.
+    ///
+    /// `cancel` is checked before each chunk of about a million numbers. A cancelled fill leaves
+    /// the sieve unfilled but keeps its progress, so any of the fill methods can pick up where it
+    /// left off.
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    ///
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(10_000_000);
+    ///
+    /// assert_eq!(my_sieve.fill_cancellable(&AtomicBool::new(true)), false);
+    /// assert!(my_sieve.lookup(2).is_err());
+    ///
+    /// assert_eq!(my_sieve.fill_cancellable(&AtomicBool::new(false)), true);
+    /// assert_eq!(my_sieve.count_primes(), 664_579);
+    /// ```
+    pub fn fill_cancellable(&mut self, cancel: &AtomicBool) -> bool {
+        while !self.filled {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            self.fill_chunk(1);
+        }
+        true
     }
 
-    #[test]
-    fn marks_correctly_med() {
-        let test_case = Sieve::new(1000);
+    /// Do up to `steps` more steps of populating an unfilled sieve, returning whether there's any
+    /// work left to do. Each step sieves a chunk of about a million numbers, or for segmented
+    /// sieves the whole (quick) fill is a single step.
+    ///
+    /// This lets a fill be spread out and interleaved with other work. Growing the sieve with
+    /// `Sieve::extend_to()` part way through is fine, but restarts the fill.
+    ///
+    /// ```
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(10_000_000);
+    ///
+    /// let mut steps = 0;
+    /// while my_sieve.fill_chunk(1) {
+    ///     steps += 1;
+    /// }
+    ///
+    /// assert!(steps > 1);
+    /// assert_eq!(my_sieve.count_primes(), 664_579);
+    /// assert_eq!(my_sieve.fill_chunk(1), false);
+    /// ```
+    pub fn fill_chunk(&mut self, steps: usize) -> bool {
+        if self.filled {
+            return false;
+        }
+        let max = self.max;
+        let table = match &mut self.table {
+            Table::Full(table) => table,
+            Table::Segmented { .. } => {
+                if steps > 0 {
+                    self.fill();
+                }
+                return !self.filled;
+            }
+        };
+        let state = self
+            .fill_state
+            .get_or_insert_with(|| FillState::new(max, 0));
+        let words = table.words_mut();
+        for _ in 0..steps {
+            let start = state.next_chunk * CHUNK_WORDS;
+            if start >= words.len() {
+                break;
+            }
+            let end = (start + CHUNK_WORDS).min(words.len());
+            sieve_chunk(
+                &mut words[start..end],
+                state.next_chunk,
+                &state.base_primes,
+                max,
+            );
+            state.next_chunk += 1;
+        }
+        if state.next_chunk * CHUNK_WORDS >= words.len() {
+            self.filled = true;
+            self.fill_state = None;
+        }
+        !self.filled
+    }
+
+    // The highest number an unfinished `fill_chunk` has sieved up to
+    fn sieved_up_to(&self) -> u64 {
+        match &self.fill_state {
+            Some(state) if state.next_chunk > 0 => {
+                let end_index = state.next_chunk * CHUNK_WORDS * bits::WORD_BITS;
+                wheel::value(end_index as u64 - 1).min(self.max)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Determine whether a number within the prime sieve's limits is trule prime or not
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, or
+    /// `Err(SieveError::OutOfBounds)` if `target > sieve.max()`.
+    ///
+    /// ```
+    /// use prime_sieve::{Sieve, SieveError};
+    ///
+    /// let my_sieve = Sieve::new(10);
+    ///
+    /// assert_eq!(my_sieve.lookup(7), Ok(true));
+    /// assert_eq!(
+    ///     my_sieve.lookup(11),
+    ///     Err(SieveError::OutOfBounds { value: 11, max: 10 })
+    /// );
+    /// ```
+    pub fn lookup(&self, target: u64) -> Result<bool, SieveError> {
+        if !self.filled {
+            Err(SieveError::NotFilled)
+        } else if target > self.max {
+            Err(SieveError::OutOfBounds {
+                value: target,
+                max: self.max,
+            })
+        } else {
+            Ok(self.is_prime_unchecked(target))
+        }
+    }
+
+    /// Determine whether `target` is prime like `Sieve::lookup()`, but returning `None` rather than
+    /// an error if the sieve can't answer (because it's unfilled or `target > sieve.max()`).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(10);
+    ///
+    /// assert_eq!(my_sieve.get(7), Some(true));
+    /// assert_eq!(my_sieve.get(11), None);
+    /// assert_eq!(prime_sieve::Sieve::unfilled(10).get(7), None);
+    /// ```
+    pub fn get(&self, target: u64) -> Option<bool> {
+        if self.filled && target <= self.max {
+            Some(self.is_prime_unchecked(target))
+        } else {
+            None
+        }
+    }
+
+    /// Determine whether `target` is prime like `Sieve::lookup()`, but without checking that the
+    /// query is valid - for hot loops whose inputs have already been checked.
+    ///
+    /// The answer is meaningless if the sieve is unfilled.
+    ///
+    /// # Safety
+    ///
+    /// `target` must be no larger than `sieve.max()`, since the table is read without bounds
+    /// checks.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let primes = (0..=my_sieve.max())
+    ///     // SAFETY: every value is within the sieve
+    ///     .filter(|&n| unsafe { my_sieve.lookup_unchecked(n) })
+    ///     .count();
+    /// assert_eq!(primes, 25);
+    /// ```
+    pub unsafe fn lookup_unchecked(&self, target: u64) -> bool {
+        debug_assert!(target <= self.max, "{} is out of bounds", target);
+        match &self.table {
+            Table::Full(table) if wheel::is_candidate(target) => {
+                // SAFETY: the caller guarantees `target <= max`, so its index is within the table
+                unsafe { table.get_unchecked(wheel::index(target) as usize) }
+            }
+            _ => self.is_prime_unchecked(target),
+        }
+    }
+
+    /// Find the prime factorization of `n`, in ascending order and with multiplicity, by trial
+    /// division against the primes in this sieve. `factorize(1)` is empty.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, `Err(SieveError::Zero)` if
+    /// `n == 0`, or `Err(SieveError::OutOfBounds)` if `n` has a factor too large for this sieve to
+    /// find (which can only happen when `n > sieve.max()²`).
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.factorize(360).unwrap(), vec![2, 2, 2, 3, 3, 5]);
+    /// assert_eq!(my_sieve.factorize(10_007).unwrap(), vec![10_007]);
+    /// assert!(my_sieve.factorize(1_000_000_007).is_err());
+    /// ```
+    pub fn factorize(&self, n: u64) -> Result<Vec<u64>, SieveError> {
+        if !self.filled {
+            return Err(SieveError::NotFilled);
+        }
+        if n == 0 {
+            return Err(SieveError::Zero);
+        }
+        let mut factors = Vec::new();
+        let mut remaining = n;
+        let mut exhausted = true;
+        for p in self.primes() {
+            if p > remaining / p {
+                exhausted = false;
+                break;
+            }
+            while remaining.is_multiple_of(p) {
+                factors.push(p);
+                remaining /= p;
+            }
+        }
+        if remaining > 1 {
+            // If every prime up to `max` was tried, only values below `(max + 1)²` must be prime
+            let limit = (u128::from(self.max) + 1).pow(2);
+            if exhausted && u128::from(remaining) >= limit {
+                return Err(SieveError::OutOfBounds {
+                    value: n,
+                    max: self.max,
+                });
+            }
+            factors.push(remaining);
+        }
+        Ok(factors)
+    }
+
+    /// Determine whether `target` is prime, falling back to a Baillie-PSW test if
+    /// the sieve can't answer (because it's unfilled or `target > sieve.max()`).
+    ///
+    /// Unlike `lookup`, this always gives an answer.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.lookup_or_test(97), true);
+    /// assert_eq!(my_sieve.lookup_or_test(1_000_000_007), true);
+    /// assert_eq!(my_sieve.lookup_or_test(1_000_000_011), false);
+    /// ```
+    pub fn lookup_or_test(&self, target: u64) -> bool {
+        self.lookup(target)
+            .unwrap_or_else(|_| primality::baillie_psw(target))
+    }
+
+    /// Takes any collection or iterator of `u64`s and returns just the prime ones, in their
+    /// original order.
+    ///
+    /// Will return `Err(SieveError::OutOfBounds)` if one of `target`'s elements is outside the
+    /// bounds of this sieve
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let filtered = my_sieve.filter(vec![1,2,3,4]).unwrap();
+    /// assert_eq!(filtered, vec![2,3]);
+    ///
+    /// let filtered = my_sieve.filter(90..100).unwrap();
+    /// assert_eq!(filtered, vec![97]);
+    /// ```
+    pub fn filter<I: IntoIterator<Item = u64>>(&self, target: I) -> Result<Vec<u64>, SieveError> {
+        let mut result: Vec<u64> = Vec::new();
+        for i in target.into_iter() {
+            if self.lookup(i)? {
+                result.push(i);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Like `Sieve::filter()`, but lazy: yields `Ok(n)` for each prime `n` in `target` and
+    /// `Err(..)` for each element outside the bounds of this sieve, skipping everything else.
+    ///
+    /// ```
+    /// use prime_sieve::SieveError;
+    ///
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let mut primes = my_sieve.filter_iter(95..);
+    /// assert_eq!(primes.next(), Some(Ok(97)));
+    /// assert_eq!(primes.next(), Some(Err(SieveError::OutOfBounds { value: 101, max: 100 })));
+    ///
+    /// let total: Result<u64, SieveError> = my_sieve.filter_iter(vec![2, 3, 4]).sum();
+    /// assert_eq!(total, Ok(5));
+    /// ```
+    pub fn filter_iter<'a, I>(
+        &'a self,
+        target: I,
+    ) -> impl Iterator<Item = Result<u64, SieveError>> + 'a
+    where
+        I: IntoIterator<Item = u64>,
+        I::IntoIter: 'a,
+    {
+        target
+            .into_iter()
+            .filter_map(move |i| match self.lookup(i) {
+                Ok(true) => Some(Ok(i)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            })
+    }
+
+    /// Like `Sieve::filter()`, but borrows its input rather than consuming it.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let candidates = [1, 2, 3, 4];
+    /// assert_eq!(my_sieve.filter_slice(&candidates).unwrap(), vec![2, 3]);
+    /// ```
+    pub fn filter_slice(&self, target: &[u64]) -> Result<Vec<u64>, SieveError> {
+        self.filter(target.iter().copied())
+    }
+
+    /// Removes all the non-prime elements from `target` in place, keeping the primes in order.
+    ///
+    /// Returns the same errors as `Sieve::filter()`, in which case `target` is left untouched.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let mut numbers = vec![1, 2, 3, 4, 5];
+    /// my_sieve.retain_primes(&mut numbers).unwrap();
+    /// assert_eq!(numbers, vec![2, 3, 5]);
+    /// ```
+    pub fn retain_primes(&self, target: &mut Vec<u64>) -> Result<(), SieveError> {
+        // Check everything up front so an error doesn't leave `target` half-filtered
+        for &i in target.iter() {
+            self.lookup(i)?;
+        }
+        target.retain(|&i| self.is_prime_unchecked(i));
+        Ok(())
+    }
+}
+
+/// Sieves are equal if they have the same max, are both filled or both unfilled, and agree on
+/// every number - how their tables are stored doesn't matter.
+///
+/// ```
+/// use prime_sieve::Sieve;
+///
+/// assert_eq!(Sieve::new(1000), Sieve::segmented(1000, 64));
+/// assert_ne!(Sieve::new(1000), Sieve::new(1001));
+/// assert_ne!(Sieve::new(1000), Sieve::unfilled(1000));
+/// ```
+impl PartialEq for Sieve {
+    fn eq(&self, other: &Sieve) -> bool {
+        if self.max != other.max || self.filled != other.filled {
+            return false;
+        }
+        match (&self.table, &other.table) {
+            // Full tables of the same max always hold the same bits for the same primes
+            (Table::Full(ours), Table::Full(theirs)) if self.filled => {
+                ours.words() == theirs.words()
+            }
+            _ => self.first_difference(other).is_none(),
+        }
+    }
+}
+
+impl Eq for Sieve {}
+
+/// Only the max and whether the sieve is filled are hashed, which is enough to tell apart every
+/// sieve that can be built with `Sieve::new()` and keeps hashing a huge sieve cheap.
+impl Hash for Sieve {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.max.hash(state);
+        self.filled.hash(state);
+    }
+}
+
+/// Iterating over a sieve by reference yields its primes, like `Sieve::primes()`.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::new(10);
+///
+/// let mut primes = Vec::new();
+/// for p in &my_sieve {
+///     primes.push(p);
+/// }
+/// assert_eq!(primes, vec![2, 3, 5, 7]);
+/// ```
+impl<'a> IntoIterator for &'a Sieve {
+    type Item = u64;
+    type IntoIter = Primes<'a>;
+
+    fn into_iter(self) -> Primes<'a> {
+        self.primes()
+    }
+}
+
+/// `sieve[n]` is shorthand for `sieve.lookup(n).unwrap()`, panicking with the error's message if
+/// the sieve is unfilled or `n` is out of bounds.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::new(100);
+///
+/// assert!(my_sieve[97]);
+/// assert!(!my_sieve[99]);
+/// ```
+impl Index<u64> for Sieve {
+    type Output = bool;
+
+    fn index(&self, target: u64) -> &bool {
+        match self.lookup(target) {
+            Ok(true) => &true,
+            Ok(false) => &false,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Build a filled sieve from a table with an entry for every number from `0`, `true` for primes
+/// (e.g. one computed elsewhere), taking the max from its length.
+///
+/// The table is checked the same way as `Sieve::from_primes()`, with `0` and `1` marked prime
+/// giving `Err(SieveError::NotPrime)`, and an empty table (which has no max) giving
+/// `Err(SieveError::InvalidPrimes)`.
+///
+/// ```
+/// use core::convert::TryFrom;
+/// use prime_sieve::{Sieve, SieveError};
+///
+/// let table = vec![false, false, true, true, false, true, false, true];
+/// let my_sieve = Sieve::try_from(table).unwrap();
+/// assert_eq!(my_sieve.max(), 7);
+/// assert_eq!(my_sieve.count_primes(), 4);
+///
+/// let table = vec![false, true, true, true];
+/// assert_eq!(Sieve::try_from(table), Err(SieveError::NotPrime { value: 1 }));
+/// ```
+impl TryFrom<Vec<bool>> for Sieve {
+    type Error = SieveError;
+
+    fn try_from(table: Vec<bool>) -> Result<Sieve, SieveError> {
+        let max = match table.len().checked_sub(1) {
+            Some(max) => max as u64,
+            None => return Err(SieveError::InvalidPrimes),
+        };
+        if table[0] {
+            return Err(SieveError::NotPrime { value: 0 });
+        }
+        let primes: Vec<u64> = table
+            .iter()
+            .enumerate()
+            .filter(|&(_, &prime)| prime)
+            .map(|(n, _)| n as u64)
+            .collect();
+        Sieve::from_primes(max, &primes)
+    }
+}
+
+/// Build a filled sieve from the primes up to some max, taking the max to be the largest of them
+/// (or `0` if there are none).
+///
+/// The primes can come in any order, and repeats are ignored. Panics if they fail the checks made
+/// by `Sieve::from_primes()` - use that directly to handle bad input.
+///
+/// ```
+/// let my_sieve: prime_sieve::Sieve = [2, 3, 5, 7, 11, 13].iter().copied().collect();
+///
+/// assert_eq!(my_sieve.max(), 13);
+/// assert_eq!(my_sieve.lookup(11), Ok(true));
+/// assert_eq!(my_sieve.lookup(12), Ok(false));
+/// ```
+impl FromIterator<u64> for Sieve {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Sieve {
+        let mut primes: Vec<u64> = iter.into_iter().collect();
+        primes.sort_unstable();
+        primes.dedup();
+        let max = primes.last().copied().unwrap_or(0);
+        Sieve::from_primes(max, &primes).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// Iterator over the primes in a sieve, in ascending order - see `Sieve::primes()`.
+///
+/// It can also run backwards, to find the largest primes without going through the rest:
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::new(1_000_000);
+///
+/// let biggest: Vec<u64> = my_sieve.primes().rev().take(3).collect();
+/// assert_eq!(biggest, vec![999_983, 999_979, 999_961]);
+/// ```
+#[derive(Debug)]
+pub struct Primes<'a>(PrimesInner<'a>);
+
+// The iteration state for each kind of table
+#[derive(Debug)]
+enum PrimesInner<'a> {
+    Full {
+        table: &'a BitTable,
+        max: u64,
+        next: u64,
+    },
+    Segmented(SegmentedPrimes<'a>),
+    Done,
+}
+
+impl Iterator for Primes<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        match &mut self.0 {
+            PrimesInner::Full { table, max, next } => {
+                for &prime in &wheel::WHEEL_PRIMES {
+                    if *next <= prime && prime <= *max {
+                        *next = prime + 1;
+                        return Some(prime);
+                    }
+                }
+                let from = usize::try_from(wheel::index_at_or_after(*next)).ok()?;
+                let index = table.next_set(from)?;
+                let prime = wheel::value(index as u64);
+                if prime > *max {
+                    return None;
+                }
+                *next = prime + 1;
+                Some(prime)
+            }
+            PrimesInner::Segmented(primes) => primes.next(),
+            PrimesInner::Done => None,
+        }
+    }
+}
+
+impl DoubleEndedIterator for Primes<'_> {
+    fn next_back(&mut self) -> Option<u64> {
+        match &mut self.0 {
+            // Iterating from the back lowers `max` as it goes
+            PrimesInner::Full { table, max, next } => {
+                if *next > *max {
+                    return None;
+                }
+                let last_index = usize::try_from(wheel::count(*max)).ok()?.checked_sub(1);
+                if let Some(index) = last_index.and_then(|i| table.prev_set(i)) {
+                    let prime = wheel::value(index as u64);
+                    if prime >= *next {
+                        *max = prime - 1;
+                        return Some(prime);
+                    }
+                }
+                for &prime in wheel::WHEEL_PRIMES.iter().rev() {
+                    if *next <= prime && prime <= *max {
+                        *max = prime - 1;
+                        return Some(prime);
+                    }
+                }
+                None
+            }
+            PrimesInner::Segmented(primes) => primes.next_back(),
+            PrimesInner::Done => None,
+        }
+    }
+}
+
+// Clear the bits for every composite in `low..=high` from a run of full table words starting at
+// wheel position `first_index` (which must be at the start of a word), given every prime up to
+// `√high`. The words must already have been through `presieve`.
+fn mark_composites(words: &mut [u64], first_index: u64, base_primes: &[u64], low: u64, high: u64) {
+    let mut clear = |index: u64| {
+        let local = (index - first_index) as usize;
+        words[local / bits::WORD_BITS] &= !(1 << (local % bits::WORD_BITS));
+    };
+    if low <= 1 && 1 <= high {
+        clear(0);
+    }
+    for &p in base_primes
+        .iter()
+        .filter(|&&p| p > presieve::LARGEST_PRESIEVED)
+    {
+        // Multiples below `p²` have a smaller prime factor, so were cleared by an earlier `p`.
+        // This can't overflow, since `p <= √max`.
+        if p * p > high {
+            break;
+        }
+        // Clear `p * q` for every wheel candidate `q >= p`, since only those are in the table
+        let first_factor = low.div_ceil(p).max(p);
+        for factor in (wheel::index_at_or_after(first_factor)..).map(wheel::value) {
+            let multiple = p * factor;
+            if multiple > high {
+                break;
+            }
+            clear(wheel::index(multiple));
+        }
+    }
+}
+
+// The largest max that `Sieve::from_primes()` sieves again to count the primes it should be given
+const FRESH_COUNT_LIMIT: u64 = 1 << 24;
+
+// How many words of a full table `sieve_chunk` is given at a time, sized so a chunk (32KiB) fits
+// in L1 cache
+const CHUNK_WORDS: usize = 4096;
+
+// Sieve chunk number `chunk_index` of a full table's words (as split by `CHUNK_WORDS`) with the
+// given max and base primes
+fn sieve_chunk(chunk: &mut [u64], chunk_index: usize, base_primes: &[u64], max: u64) {
+    let first_index = (chunk_index * CHUNK_WORDS * bits::WORD_BITS) as u64;
+    let end_index = first_index + (chunk.len() * bits::WORD_BITS) as u64;
+    let low = wheel::value(first_index);
+    let high = wheel::value(end_index - 1).min(max);
+    presieve::presieve(chunk, chunk_index * CHUNK_WORDS, max);
+    mark_composites(chunk, first_index, base_primes, low, high);
+}
+
+// The first and last values in `range`, with `start > end` if it's empty
+fn inclusive_bounds<R: RangeBounds<u64>>(range: &R) -> (u64, u64) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => start.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => Some(end),
+        Bound::Excluded(&end) => end.checked_sub(1),
+        Bound::Unbounded => Some(u64::MAX),
+    };
+    match (start, end) {
+        (Some(start), Some(end)) => (start, end),
+        // Empty ranges like `1..0`, or starting past `u64::MAX`
+        _ => (1, 0),
+    }
+}
+
+// The number of bits in a full table with the given max. Panics rather than silently truncating
+// if that can't be addressed on this platform.
+fn table_len(max: u64) -> usize {
+    usize::try_from(wheel::count(max)).expect("Sieve max is too large to address on this platform")
+}
+
+// An upper bound on the number of primes up to `n`, from Rosser and Schoenfeld's
+// `π(x) < 1.25506 x / ln x`. Works out `ln x` from the bit length so it doesn't need `std`, which
+// only loosens the bound.
+fn prime_count_upper_bound(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let ln_lower_bound = f64::from(n.ilog2()) * core::f64::consts::LN_2;
+    if ln_lower_bound < 1.0 {
+        // 2 and 3, where the rounding down costs too much
+        return n - 1;
+    }
+    (1.25506 * n as f64 / ln_lower_bound) as u64 + 1
+}
+
+// The length of a table with an entry for every number in `0..=max`, panicking like `table_len`
+fn dense_table_len(max: u64) -> usize {
+    usize::try_from(max)
+        .ok()
+        .and_then(|max| max.checked_add(1))
+        .expect("Sieve max is too large to address on this platform")
+}
+
+// The exact integer square root of `n`. Going through `f64` instead loses precision past 2⁵³ and
+// can leave out base primes.
+fn sqrt_floor(n: u64) -> u64 {
+    n.isqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert;
+    // use test::Bencher;
+
+    #[test]
+    fn marks_correctly_low() {
+        let test_case = Sieve::new(10);
+        assert!(test_case.lookup(5).unwrap());
+    }
+
+    #[test]
+    fn marks_correctly_med() {
+        let test_case = Sieve::new(1000);
         assert!(!test_case.lookup(500).unwrap());
     }
 
@@ -141,6 +1439,394 @@ mod tests {
         assert_eq!(vec![2, 3, 5], test_sieve.filter(cases).unwrap());
     }
 
+    #[test]
+    fn primes_matches_lookup() {
+        let test_sieve = Sieve::new(1000);
+        let expected: Vec<u64> = (0..=1000)
+            .filter(|&i| test_sieve.lookup(i).unwrap())
+            .collect();
+        assert_eq!(expected, test_sieve.primes().collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn unfilled_has_no_primes() {
+        let test_case = Sieve::unfilled(10);
+        assert_eq!(test_case.primes().count(), 0);
+    }
+
+    #[test]
+    fn nth_prime_large() {
+        let test_sieve = Sieve::new(105_000);
+        assert_eq!(test_sieve.nth_prime(10_000), Some(104_729));
+        assert_eq!(test_sieve.nth_prime(0), None);
+    }
+
+    #[test]
+    fn counts_primes() {
+        let test_sieve = Sieve::new(1_000_000);
+        assert_eq!(test_sieve.count_primes(), 78_498);
+        assert_eq!(test_sieve.count_primes_below(1_000_001).unwrap(), 78_498);
+        assert_eq!(test_sieve.count_primes_below(2).unwrap(), 0);
+        assert!(test_sieve.count_primes_below(1_000_002).is_err());
+    }
+
+    #[test]
+    fn next_and_prev_prime_bounds() {
+        let test_sieve = Sieve::new(13);
+        assert_eq!(test_sieve.next_prime(0), Some(2));
+        assert_eq!(test_sieve.next_prime(12), Some(13));
+        assert_eq!(test_sieve.next_prime(13), None);
+        assert_eq!(test_sieve.prev_prime(14), Some(13));
+        assert_eq!(test_sieve.prev_prime(15), None);
+        assert_eq!(Sieve::unfilled(13).next_prime(0), None);
+
+        let full = Sieve::new(1000);
+        let segmented = Sieve::segmented(1000, 16);
+        for n in 0..=1001 {
+            let expected = (0..n).rev().find(|&i| full.lookup(i) == Ok(true));
+            assert_eq!(full.prev_prime(n), expected, "n {}", n);
+            assert_eq!(segmented.prev_prime(n), expected, "n {}", n);
+        }
+    }
+
+    #[test]
+    fn segmented_matches_full() {
+        let full = Sieve::new(10_000);
+        for &segment_size in &[1, 7, 100, 20_000] {
+            let segmented = Sieve::segmented(10_000, segment_size);
+            assert!(full.primes().eq(segmented.primes()));
+            assert_eq!(full.next_prime(5_000), segmented.next_prime(5_000));
+            assert_eq!(full.prev_prime(5_000), segmented.prev_prime(5_000));
+            for i in 0..=10_000 {
+                assert_eq!(full.lookup(i), segmented.lookup(i));
+            }
+        }
+    }
+
+    #[test]
+    fn tiny_sieves() {
+        assert_eq!(Sieve::new(0).count_primes(), 0);
+        assert_eq!(Sieve::new(1).count_primes(), 0);
+        assert_eq!(Sieve::segmented(2, 1).count_primes(), 1);
+    }
+
+    #[test]
+    fn matches_naive_for_every_small_max() {
+        let is_prime = |n: u64| n >= 2 && (2..n).all(|d| !n.is_multiple_of(d));
+        for max in 0..50 {
+            let test_sieve = Sieve::new(max);
+            for i in 0..=max {
+                assert_eq!(
+                    test_sieve.lookup(i).unwrap(),
+                    is_prime(i),
+                    "{} in {}",
+                    i,
+                    max
+                );
+            }
+            assert!(test_sieve.primes().eq((0..=max).filter(|&i| is_prime(i))));
+        }
+    }
+
+    #[test]
+    fn atkin_matches_eratosthenes() {
+        for max in (0..100).chain(vec![1_000_000, 1_000_003]) {
+            let atkin = Sieve::new_atkin(max);
+            assert_eq!(
+                atkin.first_difference(&Sieve::new(max)),
+                None,
+                "max {}",
+                max
+            );
+            assert_eq!(atkin.count_primes(), Sieve::new(max).count_primes());
+        }
+    }
+
+    #[test]
+    fn extending_matches_new() {
+        for &(from, to) in &[
+            (0, 1),
+            (1, 100),
+            (10, 10_000),
+            (5_000, 5_001),
+            (100_000, 1_000_000),
+        ] {
+            let mut extended = Sieve::new(from);
+            extended.extend_to(to);
+            assert_eq!(extended.max(), to);
+            assert_eq!(extended.first_difference(&Sieve::new(to)), None);
+            assert_eq!(extended.count_primes(), Sieve::new(to).count_primes());
+
+            let mut segmented = Sieve::segmented(from, 100);
+            segmented.extend_to(to);
+            assert_eq!(segmented.count_primes(), Sieve::new(to).count_primes());
+        }
+        let mut unfilled = Sieve::unfilled(10);
+        unfilled.extend_to(100);
+        unfilled.fill();
+        assert_eq!(unfilled.count_primes(), 25);
+    }
+
+    #[test]
+    fn errors_are_matchable() {
+        assert_eq!(Sieve::unfilled(10).lookup(5), Err(SieveError::NotFilled));
+        assert_eq!(
+            Sieve::new(10).filter(vec![2, 30]),
+            Err(SieveError::OutOfBounds { value: 30, max: 10 })
+        );
+        assert_eq!(
+            SieveError::OutOfBounds { value: 30, max: 10 }.to_string(),
+            "30 is out of this sieve's bounds (max 10)"
+        );
+    }
+
+    #[test]
+    fn factorizes_up_to_max_squared() {
+        let test_sieve = Sieve::new(10);
+        assert!(test_sieve.factorize(1).unwrap().is_empty());
+        assert_eq!(test_sieve.factorize(120).unwrap(), vec![2, 2, 2, 3, 5]);
+        // 11 * 11 needs 11 in the sieve, but 113 can't have a factor above 10
+        assert!(test_sieve.factorize(121).is_err());
+        assert_eq!(test_sieve.factorize(113).unwrap(), vec![113]);
+        assert_eq!(test_sieve.factorize(2 * 113).unwrap(), vec![2, 113]);
+        assert_eq!(test_sieve.factorize(0), Err(SieveError::Zero));
+        assert_eq!(Sieve::unfilled(10).factorize(4), Err(SieveError::NotFilled));
+        for n in 1..10_000 {
+            let factors = Sieve::new(100).factorize(n).unwrap();
+            assert_eq!(factors.iter().product::<u64>(), n);
+        }
+    }
+
+    #[test]
+    fn range_bounds_are_respected() {
+        let test_sieve = Sieve::new(100);
+        assert!(test_sieve.primes_in_range(..).eq(test_sieve.primes()));
+        assert!(test_sieve.primes_in_range(7..=11).eq(vec![7, 11]));
+        assert!(test_sieve.primes_in_range(7..11).eq(vec![7]));
+        assert_eq!(test_sieve.primes_in_range(11..11).count(), 0);
+        assert_eq!(
+            test_sieve
+                .primes_in_range((Bound::Included(50), Bound::Excluded(10)))
+                .count(),
+            0
+        );
+        assert_eq!(test_sieve.primes_in_range(98..1000).count(), 0);
+        let segmented = Sieve::segmented(100, 8);
+        assert!(segmented
+            .primes_in_range(40..60)
+            .eq(test_sieve.primes_in_range(40..60)));
+    }
+
+    #[test]
+    fn retain_leaves_input_on_error() {
+        let test_sieve = Sieve::new(10);
+        let mut numbers = vec![2, 4, 11];
+        assert!(test_sieve.retain_primes(&mut numbers).is_err());
+        assert_eq!(numbers, vec![2, 4, 11]);
+        numbers.pop();
+        test_sieve.retain_primes(&mut numbers).unwrap();
+        assert_eq!(numbers, vec![2]);
+    }
+
+    #[test]
+    fn progress_fill_matches_fill() {
+        for &max in &[0, 1, 241, 1_000_003, 2_000_000] {
+            let mut sieve = Sieve::unfilled(max);
+            let mut last = 0;
+            sieve.fill_with_progress(|done, total| {
+                assert!(last <= done && done <= total);
+                last = done;
+            });
+            assert_eq!(last, max);
+            assert_eq!(
+                sieve.first_difference(&Sieve::new(max)),
+                None,
+                "max {}",
+                max
+            );
+        }
+
+        for (steps, expected) in [(1, 11), (3, 4), (10, 2), (11, 1), (usize::MAX, 1)] {
+            let mut sieve = Sieve::unfilled(10_000_000);
+            let mut reports = Vec::new();
+            sieve.fill_with_progress_every(steps, |done, _| reports.push(done));
+            assert_eq!(reports.len(), expected, "steps {}", steps);
+            assert_eq!(reports.last(), Some(&10_000_000));
+            assert_eq!(sieve.count_primes(), 664_579);
+        }
+    }
+
+    #[test]
+    fn cancelled_fill_resumes() {
+        use std::sync::atomic::AtomicBool;
+
+        let cancel = AtomicBool::new(false);
+        let mut sieve = Sieve::unfilled(10_000_000);
+        sieve.fill_chunk(3);
+        cancel.store(true, Ordering::Relaxed);
+        assert!(!sieve.fill_cancellable(&cancel));
+        assert_eq!(
+            sieve.sieved_up_to(),
+            wheel::value(3 * CHUNK_WORDS as u64 * 64 - 1)
+        );
+        cancel.store(false, Ordering::Relaxed);
+        assert!(sieve.fill_cancellable(&cancel));
+        assert_eq!(sieve, Sieve::new(10_000_000));
+        assert!(sieve.fill_cancellable(&AtomicBool::new(true)));
+
+        let mut segmented = Sieve::unfilled_segmented(1_000_000, 100);
+        assert!(!segmented.fill_cancellable(&AtomicBool::new(true)));
+        assert!(segmented.fill_cancellable(&cancel));
+        assert_eq!(segmented.count_primes(), 78_498);
+    }
+
+    #[test]
+    fn equal_sieves_hash_equally() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |sieve: &Sieve| {
+            let mut hasher = DefaultHasher::new();
+            sieve.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut extended = Sieve::new(100);
+        extended.extend_to(5000);
+        let fresh = Sieve::new(5000);
+        assert_eq!(extended, fresh);
+        assert_eq!(hash(&extended), hash(&fresh));
+        assert_eq!(Sieve::unfilled(10), Sieve::unfilled(10));
+    }
+
+    #[test]
+    fn prime_count_bound_holds() {
+        let sieve = Sieve::new(100_000);
+        let mut count = 0;
+        for n in 0..=100_000 {
+            if sieve.lookup(n).unwrap() {
+                count += 1;
+            }
+            assert!(prime_count_upper_bound(n) >= count, "n {}", n);
+        }
+        let primes = sieve.primes_vec();
+        assert_eq!(primes.len(), 9592);
+        assert!(primes.capacity() < 2 * primes.len());
+        assert!(Sieve::unfilled(100).primes_vec().is_empty());
+    }
+
+    #[test]
+    fn primes_run_backwards() {
+        for &max in &[0, 1, 2, 3, 6, 7, 100, 10_007] {
+            for sieve in [Sieve::new(max), Sieve::segmented(max, 64)] {
+                let forwards = sieve.primes_vec();
+                let mut backwards: Vec<u64> = sieve.primes().rev().collect();
+                backwards.reverse();
+                assert_eq!(forwards, backwards, "max {}", max);
+
+                // Meeting in the middle yields everything exactly once
+                let mut primes = sieve.primes();
+                let mut seen = Vec::new();
+                while let Some(p) = primes.next() {
+                    seen.push(p);
+                    seen.extend(primes.next_back());
+                }
+                seen.sort_unstable();
+                assert_eq!(seen, forwards, "max {}", max);
+            }
+        }
+        let sieve = Sieve::new(100);
+        let range: Vec<u64> = sieve.primes_in_range(3..=30).rev().collect();
+        assert_eq!(range, vec![29, 23, 19, 17, 13, 11, 7, 5, 3]);
+    }
+
+    #[test]
+    fn sums_primes() {
+        let test_sieve = Sieve::new(2_000_000);
+        assert_eq!(test_sieve.sum_primes(), 142_913_828_922);
+        assert_eq!(test_sieve.sum_primes_below(0).unwrap(), 0);
+        assert_eq!(test_sieve.sum_primes_below(3).unwrap(), 2);
+        assert_eq!(Sieve::unfilled(10).sum_primes(), 0);
+    }
+
+    #[test]
+    fn primorial_limits() {
+        let test_sieve = Sieve::new(110);
+        assert_eq!(test_sieve.primorial(0), Ok(1));
+        assert_eq!(test_sieve.primorial(1), Ok(1));
+        assert_eq!(test_sieve.primorial(102).unwrap() % 101, 0);
+        assert_eq!(
+            test_sieve.primorial(111),
+            Err(SieveError::OutOfBounds {
+                value: 111,
+                max: 110
+            })
+        );
+    }
+
+    #[test]
+    fn truncating_matches_new() {
+        for (from, to) in [
+            (1_000_000, 0),
+            (1_000_000, 1),
+            (1_000_000, 12_345),
+            (100, 99),
+        ] {
+            let mut truncated = Sieve::new(from);
+            truncated.truncate(to);
+            assert_eq!(truncated, Sieve::new(to));
+            assert_eq!(truncated.heap_size(), Sieve::new(to).heap_size());
+
+            let mut segmented = Sieve::segmented(from, 1000);
+            segmented.truncate(to);
+            assert_eq!(segmented, Sieve::new(to));
+            assert_eq!(
+                segmented.heap_size(),
+                Sieve::segmented(to, 1000).heap_size()
+            );
+        }
+
+        // Truncating part way through a fill
+        let mut partial = Sieve::unfilled(10_000_000);
+        partial.fill_chunk(1);
+        partial.truncate(5_000_000);
+        partial.fill();
+        assert_eq!(partial.count_primes(), 348_513);
+
+        let mut unchanged = Sieve::new(100);
+        unchanged.truncate(1000);
+        assert_eq!(unchanged.max(), 100);
+    }
+
+    #[test]
+    fn merges_sieves() {
+        let expected = Sieve::new(100_000);
+        for (a, b) in [
+            (Sieve::new(100_000), Sieve::new(10)),
+            (Sieve::new(1000), Sieve::unfilled(100_000)),
+            (Sieve::unfilled(100_000), Sieve::new(100_000)),
+            (Sieve::segmented(5000, 64), Sieve::unfilled(100_000)),
+            (Sieve::unfilled(10), Sieve::new(100_000)),
+        ] {
+            assert_eq!(a.merge(b), expected);
+        }
+        assert_eq!(
+            Sieve::unfilled(10).merge(Sieve::unfilled(100)),
+            Sieve::unfilled(100)
+        );
+    }
+
+    #[test]
+    fn chunked_fill_survives_extending() {
+        let mut sieve = Sieve::unfilled(3_000_000);
+        assert!(sieve.fill_chunk(1));
+        assert!(sieve.lookup(7).is_err());
+        sieve.extend_to(5_000_000);
+        while sieve.fill_chunk(2) {}
+        assert_eq!(sieve.first_difference(&Sieve::new(5_000_000)), None);
+
+        let mut segmented = Sieve::segmented(1000, 10);
+        assert!(!segmented.fill_chunk(1));
+    }
+
     #[test]
     #[should_panic]
     fn panics_on_invalid_lookup() {
@@ -148,6 +1834,138 @@ mod tests {
         test_case.lookup(100).unwrap();
     }
 
+    #[test]
+    fn from_primes_round_trips() {
+        for &max in &[0, 1, 2, 4, 5, 6, 10_000] {
+            let primes: Vec<u64> = Sieve::new(max).primes().collect();
+            let rebuilt = Sieve::from_primes(max, &primes).unwrap();
+            assert_eq!(rebuilt, Sieve::new(max), "max {}", max);
+        }
+        let out_of_bounds = SieveError::OutOfBounds { value: 11, max: 10 };
+        assert_eq!(Sieve::from_primes(10, &[2, 3, 5, 11]), Err(out_of_bounds));
+        assert_eq!(
+            Sieve::from_primes(10, &[2, 3, 5, 7, 7]),
+            Err(SieveError::InvalidPrimes)
+        );
+        assert_eq!(
+            Sieve::from_primes(10, &[3, 5, 7]),
+            Err(SieveError::InvalidPrimes)
+        );
+        assert_eq!(
+            Sieve::from_primes(10, &[1, 2, 3, 5, 7]),
+            Err(SieveError::NotPrime { value: 1 })
+        );
+        assert_eq!(
+            Sieve::from_primes(10, &[2, 3, 4]),
+            Err(SieveError::NotPrime { value: 4 })
+        );
+        // Composites and missing primes that the wheel can't rule out
+        assert_eq!(
+            Sieve::from_primes(100, &[2, 3, 5, 49]),
+            Err(SieveError::NotPrime { value: 49 })
+        );
+        let without_7: Vec<u64> = Sieve::new(100).primes().filter(|&p| p != 7).collect();
+        assert_eq!(
+            Sieve::from_primes(100, &without_7),
+            Err(SieveError::InvalidPrimes)
+        );
+        let without_97: Vec<u64> = Sieve::new(100).primes().filter(|&p| p != 97).collect();
+        assert_eq!(
+            Sieve::from_primes(100, &without_97),
+            Err(SieveError::InvalidPrimes)
+        );
+        // Above the limit only big gaps are noticed
+        #[cfg(feature = "std")]
+        assert_eq!(
+            Sieve::from_primes(FRESH_COUNT_LIMIT + 1, &[2, 3, 5, 7]),
+            Err(SieveError::InvalidPrimes)
+        );
+    }
+
+    #[test]
+    fn converts_from_bool_tables() {
+        for &max in &[0, 1, 2, 4, 5, 6, 10_000] {
+            let sieve = Sieve::new(max);
+            let table: Vec<bool> = (0..=max).map(|n| sieve[n]).collect();
+            assert_eq!(Sieve::try_from(table), Ok(sieve), "max {}", max);
+        }
+        let mut table = vec![false; 11];
+        assert_eq!(
+            Sieve::try_from(table.clone()),
+            Err(SieveError::InvalidPrimes)
+        );
+        table[0] = true;
+        assert_eq!(
+            Sieve::try_from(table.clone()),
+            Err(SieveError::NotPrime { value: 0 })
+        );
+        table[0] = false;
+        for p in [2, 3, 5, 7, 9] {
+            table[p] = true;
+        }
+        assert_eq!(
+            Sieve::try_from(table),
+            Err(SieveError::NotPrime { value: 9 })
+        );
+        assert_eq!(Sieve::try_from(vec![]), Err(SieveError::InvalidPrimes));
+    }
+
+    #[test]
+    fn collects_from_primes() {
+        let sieve: Sieve = Sieve::new(10_000).primes().rev().collect();
+        assert_eq!(sieve, Sieve::new(9973));
+        let repeated: Sieve = [5, 3, 2, 3, 7].iter().copied().collect();
+        assert_eq!(repeated, Sieve::new(7));
+        assert_eq!(core::iter::empty().collect::<Sieve>(), Sieve::new(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "9 is not prime")]
+    fn collecting_composites_panics() {
+        let _: Sieve = [2, 3, 5, 7, 9].iter().copied().collect();
+    }
+
+    #[test]
+    fn sqrt_floor_is_exact_at_boundaries() {
+        for &root in &[1u64 << 26, 94_906_265, 94_906_266, (1 << 32) - 1] {
+            let square = root * root;
+            assert_eq!(sqrt_floor(square), root);
+            assert_eq!(sqrt_floor(square - 1), root - 1);
+            assert_eq!(sqrt_floor(square + 1), root);
+        }
+        assert_eq!(sqrt_floor(1 << 53), 94_906_265);
+        assert_eq!(sqrt_floor(u64::MAX), (1 << 32) - 1);
+        assert_eq!(sqrt_floor(0), 0);
+    }
+
+    #[test]
+    fn sieves_square_of_prime_past_f64_precision() {
+        // The first prime whose square is beyond 2⁵³, so every integer isn't an exact `f64`
+        let p = 94_906_297;
+        let sieve = Sieve::segmented(p * p, 1 << 16);
+        assert_eq!(sieve.lookup(p * p), Ok(false));
+        assert_eq!(sieve.prev_prime(p * p), Some(p * p - 66));
+    }
+
+    #[test]
+    fn unchecked_lookup_matches_lookup() {
+        for sieve in [Sieve::new(1000), Sieve::segmented(1000, 64)] {
+            for n in 0..=1000 {
+                // SAFETY: `n` is within the sieve
+                let unchecked = unsafe { sieve.lookup_unchecked(n) };
+                assert_eq!(Ok(unchecked), sieve.lookup(n), "n {}", n);
+                assert_eq!(Some(unchecked), sieve.get(n), "n {}", n);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "100 is out of this sieve's bounds (max 10)")]
+    fn panics_on_invalid_index() {
+        let test_case = Sieve::new(10);
+        let _ = test_case[100];
+    }
+
     #[test]
     #[should_panic]
     fn panics_on_unfilled_lookup() {
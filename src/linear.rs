@@ -0,0 +1,68 @@
+//! The linear sieve of Euler, which finds smallest prime factors in `O(n)` time.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::dense_table_len;
+
+/// Find the smallest prime factor of every composite up to `max`, with `0` for primes, `0` and
+/// `1` (as `FactorSieve` stores them).
+///
+/// Each composite `n` is marked exactly once, as `p · (n / p)` where `p` is its smallest prime
+/// factor, so unlike the Sieve of Eratosthenes no work is repeated. That needs the list of primes
+/// found so far, which is thrown away at the end.
+pub(crate) fn smallest_factors(max: u64) -> Vec<u32> {
+    let mut smallest_factors = vec![0u32; dense_table_len(max)];
+    let mut primes: Vec<u32> = Vec::new();
+    for i in 2..smallest_factors.len() {
+        let largest_allowed = match smallest_factors[i] {
+            0 => {
+                // A prime's multiples only need marking while they can be in the table, and the
+                // marking factor is always at most `√max`, so fits in a `u32`
+                if let Ok(p) = u32::try_from(i) {
+                    primes.push(p);
+                }
+                i
+            }
+            p => p as usize,
+        };
+        // Multiplying by a prime bigger than `i`'s smallest factor would mark a composite whose
+        // smallest factor isn't the multiplier, which is left for its own turn
+        for &p in &primes {
+            let p = p as usize;
+            if p > largest_allowed {
+                break;
+            }
+            match i.checked_mul(p) {
+                Some(multiple) if multiple < smallest_factors.len() => {
+                    smallest_factors[multiple] = p as u32;
+                }
+                _ => break,
+            }
+        }
+    }
+    smallest_factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sieve;
+
+    #[test]
+    fn finds_smallest_factors() {
+        let sieve = Sieve::new(100_000);
+        let smallest = smallest_factors(100_000);
+        for n in 2..=100_000 {
+            let expected = sieve.factorize(n).unwrap()[0];
+            if expected == n {
+                assert_eq!(smallest[n as usize], 0, "{}", n);
+            } else {
+                assert_eq!(u64::from(smallest[n as usize]), expected, "{}", n);
+            }
+        }
+        assert_eq!(smallest_factors(0), vec![0]);
+        assert_eq!(smallest_factors(4), vec![0, 0, 0, 0, 2]);
+    }
+}
@@ -1,14 +1,595 @@
-use prime_sieve::Sieve;
+use clap::error::ErrorKind;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use indicatif::{ProgressBar, ProgressStyle};
+use prime_sieve::{Sieve, SieveCache};
+use std::convert::TryFrom;
 use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+
+/// How many numbers `list` sieves at a time, so output starts straight away
+const LIST_SEGMENT_SIZE: u64 = 1 << 18;
+
+/// Sieves at least this big show a progress bar while they fill
+const PROGRESS_THRESHOLD: u64 = 1 << 26;
+
+/// If set, full sieves are saved in and loaded from a `SieveCache` in this directory
+const CACHE_VAR: &str = "PRIME_SIEVE_CACHE";
+
+/// `check` only sieves numbers up to this (and only with a cache to keep the sieve in), since
+/// Baillie-PSW answers for anything bigger without allocating a table
+const CHECK_SIEVE_LIMIT: u64 = 1 << 32;
+
+/// The exit code for failures other than bad arguments, which exit with clap's `2`
+const FAILURE: i32 = 1;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        panic!("Too few args passed!");
+    let matches = cli().get_matches();
+    match run(&matches) {
+        // Stop quietly if the reader goes away, e.g. when piped into `head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(FAILURE);
+        }
+        Ok(()) => {}
+    }
+}
+
+/// The command line interface, with a subcommand for each thing the binary can do.
+fn cli() -> Command {
+    let number = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .help(help)
+            .required(true)
+            .value_parser(value_parser!(u64))
+    };
+    let flag = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .help(help)
+            .action(ArgAction::SetTrue)
+    };
+    Command::new("prime_sieve")
+        .about("Find, count and test prime numbers with a sieve")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help(
+                    "How to write the results: text, json, or (for list, with -o) the sieve \
+                     itself in the binary format of Sieve::write_to",
+                )
+                .value_parser(["text", "json", "sieve"])
+                .default_value("text")
+                .global(true),
+        )
+        .arg(flag("json", "Print the results as JSON, like --format json").global(true))
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write the results to FILE instead of stdout")
+                .value_parser(value_parser!(PathBuf))
+                .global(true),
+        )
+        .after_help(
+            "Set PRIME_SIEVE_CACHE to a directory to reuse sieves between runs.\n\
+             Exits with 2 if the arguments are invalid, or 1 if anything else goes wrong.",
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Test whether a number is prime")
+                .arg(number("number", "The number to test")),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List every prime up to a maximum")
+                .arg(number("max", "The largest number to consider")),
+        )
+        .subcommand(
+            Command::new("count")
+                .about("Count the primes up to a maximum")
+                .arg(number("max", "The largest number to consider")),
+        )
+        .subcommand(
+            Command::new("nth")
+                .about("Find the nth prime, counting 2 as the first")
+                .arg(number("n", "Which prime to find")),
+        )
+        .subcommand(
+            Command::new("gaps")
+                .about("List each prime up to a maximum with the gap to the next one")
+                .arg(number("max", "The largest number to consider"))
+                .arg(flag("summary", "Only show the largest gap")),
+        )
+        .subcommand(
+            Command::new("factor")
+                .about("Find the prime factors of a number")
+                .arg(
+                    number("number", "The number to factorize")
+                        .value_parser(value_parser!(u64).range(1..)),
+                ),
+        )
+        .subcommand(
+            Command::new("filter")
+                .about("Print the primes among whitespace-separated numbers read from stdin")
+                .arg(flag(
+                    "annotate",
+                    "Print every number, each marked prime, composite or neither",
+                )),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Time how long sieving up to a maximum takes")
+                .arg(number("max", "The largest number to sieve"))
+                .arg(flag(
+                    "compare",
+                    "Time every backend, not just the default one",
+                )),
+        )
+}
+
+/// Run the subcommand picked out by `matches`.
+fn run(matches: &ArgMatches) -> io::Result<()> {
+    let (name, args) = matches.subcommand().expect("a subcommand is required");
+    let format = args
+        .get_one::<String>("format")
+        .expect("the format has a default");
+    let json = args.get_flag("json") || format == "json";
+    let number = |name| *args.get_one::<u64>(name).expect("numbers are required");
+    let flag = |name| args.get_flag(name);
+
+    if format == "sieve" {
+        if name != "list" {
+            usage_error("--format sieve only works with list");
+        }
+        if json {
+            usage_error("--format sieve can't be combined with --json");
+        }
+        return match args.get_one::<PathBuf>("output") {
+            Some(path) => write_sieve(path, number("max")),
+            None => usage_error("--format sieve needs -o FILE to write the table to"),
+        };
     }
-    let to_test: u64 = args[1].parse().unwrap();
-    match Sieve::new(to_test).lookup(to_test).unwrap() {
-        true => println!("{} is prime", to_test),
-        false => println!("{} is not prime", to_test),
+
+    let stdout = io::stdout();
+    let mut out: BufWriter<Box<dyn Write>> = match args.get_one::<PathBuf>("output") {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("couldn't create {}: {}", path.display(), e),
+                )
+            })?;
+            BufWriter::new(Box::new(file))
+        }
+        None => BufWriter::new(Box::new(stdout.lock())),
     };
+    match name {
+        "check" => check(&mut out, json, number("number")),
+        "list" => list(&mut out, json, number("max")),
+        "count" => count(&mut out, json, number("max")),
+        "nth" => nth(&mut out, json, number("n")),
+        "gaps" => gaps(&mut out, json, flag("summary"), number("max")),
+        "factor" => factor(&mut out, json, number("number")),
+        "filter" => filter(&mut out, json, flag("annotate")),
+        "bench" => bench(&mut out, json, flag("compare"), number("max")),
+        other => unreachable!("unknown subcommand {:?}", other),
+    }?;
+    out.flush()
+}
+
+/// Report a problem with the arguments that clap can't check by itself, exiting like clap does.
+fn usage_error(message: &str) -> ! {
+    cli().error(ErrorKind::ArgumentConflict, message).exit()
+}
+
+/// Save a full sieve of every number up to `max` to `path`, for `Sieve::read_from()` to load.
+fn write_sieve(path: &Path, max: u64) -> io::Result<()> {
+    filled_sieve(max)?.write_to(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("couldn't write {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Create and populate a full sieve, drawing a progress bar on stderr (if it's a terminal) for
+/// big ones. Uses the cache in `$PRIME_SIEVE_CACHE` if there is one.
+fn filled_sieve(max: u64) -> io::Result<Sieve> {
+    let cache = match env::var_os(CACHE_VAR) {
+        Some(dir) => Some(SieveCache::new(dir)?),
+        None => None,
+    };
+    if max < PROGRESS_THRESHOLD {
+        return match cache {
+            Some(cache) => cache.get(max),
+            None => Ok(Sieve::new(max)),
+        };
+    }
+    let style = ProgressStyle::with_template("{bar:40} {percent}% sieved, ETA {eta}").unwrap();
+    let bar = ProgressBar::new(max).with_style(style);
+    let progress = |done, _| bar.set_position(done);
+    let sieve = match cache {
+        Some(cache) => cache.get_with_progress(max, progress)?,
+        None => {
+            let mut sieve = Sieve::unfilled(max);
+            sieve.fill_with_progress(progress);
+            sieve
+        }
+    };
+    bar.finish_and_clear();
+    Ok(sieve)
+}
+
+/// Print whether `n` is prime, from the sieve cache if there is one and `n` isn't too big for it.
+fn check(out: &mut impl Write, json: bool, n: u64) -> io::Result<()> {
+    let prime = if env::var_os(CACHE_VAR).is_some() && n <= CHECK_SIEVE_LIMIT {
+        filled_sieve(n)?.lookup(n).unwrap()
+    } else {
+        prime_sieve::is_prime(n)
+    };
+    match (json, prime) {
+        (true, _) => writeln!(out, r#"{{"number":{},"prime":{}}}"#, n, prime),
+        (false, true) => writeln!(out, "{} is prime", n),
+        (false, false) => writeln!(out, "{} is not prime", n),
+    }
+}
+
+fn list(out: &mut impl Write, json: bool, max: u64) -> io::Result<()> {
+    let sieve = Sieve::segmented(max, LIST_SEGMENT_SIZE);
+    let primes = sieve.primes();
+    if !json {
+        for p in primes {
+            writeln!(out, "{}", p)?;
+        }
+        return Ok(());
+    }
+    write!(out, "[")?;
+    for (i, p) in primes.enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{}", p)?;
+    }
+    writeln!(out, "]")
+}
+
+fn count(out: &mut impl Write, json: bool, max: u64) -> io::Result<()> {
+    let count = filled_sieve(max)?.count_primes();
+    if json {
+        writeln!(out, r#"{{"max":{},"count":{}}}"#, max, count)
+    } else {
+        writeln!(out, "{}", count)
+    }
+}
+
+/// Print every prime up to `max` (except the last) with the gap to the next one, or with
+/// `summary` just the largest gap.
+fn gaps(out: &mut impl Write, json: bool, summary: bool, max: u64) -> io::Result<()> {
+    let sieve = Sieve::segmented(max, LIST_SEGMENT_SIZE);
+    if summary {
+        return match (json, sieve.max_gap()) {
+            (true, Some((p, gap))) => writeln!(
+                out,
+                r#"{{"max":{},"largest_gap":{{"prime":{},"gap":{}}}}}"#,
+                max, p, gap
+            ),
+            (true, None) => writeln!(out, r#"{{"max":{},"largest_gap":null}}"#, max),
+            (false, Some((p, gap))) => {
+                writeln!(out, "The largest gap is {}, from {} to {}", gap, p, p + gap)
+            }
+            (false, None) => writeln!(out, "There are fewer than two primes up to {}", max),
+        };
+    }
+    if !json {
+        for (p, gap) in sieve.gaps() {
+            writeln!(out, "{} {}", p, gap)?;
+        }
+        return Ok(());
+    }
+    write!(out, "[")?;
+    for (i, (p, gap)) in sieve.gaps().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, r#"{{"prime":{},"gap":{}}}"#, p, gap)?;
+    }
+    writeln!(out, "]")
+}
+
+/// Print the `n`th prime, sieving up to a bound that's sure to contain it.
+fn nth(out: &mut impl Write, json: bool, n: u64) -> io::Result<()> {
+    let prime = match n {
+        0 => None,
+        _ => filled_sieve(prime_sieve::estimates::nth_prime_upper_bound(n))?
+            .nth_prime(usize::try_from(n).expect("n is too large for this platform")),
+    };
+    match (json, prime) {
+        (true, Some(p)) => writeln!(out, r#"{{"n":{},"prime":{}}}"#, n, p),
+        (true, None) => writeln!(out, r#"{{"n":{},"prime":null}}"#, n),
+        (false, Some(p)) => writeln!(out, "{}", p),
+        (false, None) => writeln!(out, "There is no prime number {}", n),
+    }
+}
+
+/// Print the prime factors of `n` (which mustn't be `0`), with multiplicity.
+fn factor(out: &mut impl Write, json: bool, n: u64) -> io::Result<()> {
+    let factors: Vec<String> = prime_factors(n).iter().map(|p| p.to_string()).collect();
+    if json {
+        writeln!(
+            out,
+            r#"{{"number":{},"factors":[{}]}}"#,
+            n,
+            factors.join(",")
+        )
+    } else if factors.is_empty() {
+        writeln!(out, "{} = 1", n)
+    } else {
+        writeln!(out, "{} = {}", n, factors.join(" * "))
+    }
+}
+
+/// Factorize `n` by trial division with the primes up to `√n`, giving no factors for `0` or `1`.
+///
+/// The primes are sieved a segment at a time, and what's left is tested for primality whenever a
+/// factor is divided out, so a big prime cofactor ends the search without sieving all the way up
+/// to its square root.
+fn prime_factors(n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut remaining = n;
+    if !prime_sieve::is_prime(n) {
+        for p in Sieve::segmented(n.isqrt(), LIST_SEGMENT_SIZE).primes() {
+            if p > remaining / p {
+                break;
+            }
+            if remaining.is_multiple_of(p) {
+                while remaining.is_multiple_of(p) {
+                    factors.push(p);
+                    remaining /= p;
+                }
+                if prime_sieve::is_prime(remaining) {
+                    break;
+                }
+            }
+        }
+    }
+    if remaining > 1 {
+        factors.push(remaining);
+    }
+    factors
+}
+
+/// Read whitespace-separated numbers from stdin and print the prime ones, or with `annotate`
+/// print every number alongside whether it's prime.
+fn filter(out: &mut impl Write, json: bool, annotate: bool) -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut written = 0;
+    if json {
+        write!(out, "[")?;
+    }
+    for line in stdin.lock().lines() {
+        let line = line?;
+        for token in line.split_whitespace() {
+            let n: u64 = token.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{:?} is not a valid number", token),
+                )
+            })?;
+            let prime = prime_sieve::is_prime(n);
+            if !annotate && !prime {
+                continue;
+            }
+            if json && written > 0 {
+                write!(out, ",")?;
+            }
+            match (json, annotate, prime) {
+                (true, true, _) => write!(out, r#"{{"number":{},"prime":{}}}"#, n, prime)?,
+                (true, false, _) => write!(out, "{}", n)?,
+                (false, true, true) => writeln!(out, "{} prime", n)?,
+                (false, true, false) if n < 2 => writeln!(out, "{} neither", n)?,
+                (false, true, false) => writeln!(out, "{} composite", n)?,
+                (false, false, _) => writeln!(out, "{}", n)?,
+            }
+            written += 1;
+        }
+    }
+    if json {
+        writeln!(out, "]")?;
+    }
+    Ok(())
+}
+
+/// A way of creating a filled sieve with a given max
+type Backend = fn(u64) -> Sieve;
+
+/// Time how long each way of building a sieve takes to find every prime up to `max`, either just
+/// the default one or (with `compare`) every backend available.
+fn bench(out: &mut impl Write, json: bool, compare: bool, max: u64) -> io::Result<()> {
+    let mut backends: Vec<(&str, Backend)> = vec![("eratosthenes", Sieve::new)];
+    if compare {
+        backends.push(("atkin", Sieve::new_atkin));
+        backends.push(("segmented", |max| Sieve::segmented(max, LIST_SEGMENT_SIZE)));
+        #[cfg(feature = "rayon")]
+        backends.push(("parallel", |max| {
+            let mut sieve = Sieve::unfilled(max);
+            sieve.fill_parallel();
+            sieve
+        }));
+    }
+
+    if json {
+        write!(out, "[")?;
+    } else {
+        writeln!(
+            out,
+            "{:<14}{:>12}{:>16}{:>14}",
+            "backend", "seconds", "primes/sec", "memory"
+        )?;
+    }
+    for (i, (name, build)) in backends.into_iter().enumerate() {
+        let start = Instant::now();
+        let sieve = build(max);
+        let count = sieve.count_primes();
+        let seconds = start.elapsed().as_secs_f64();
+        // Keep the JSON valid even if the clock is too coarse to see anything
+        let rate = if seconds > 0.0 {
+            count as f64 / seconds
+        } else {
+            0.0
+        };
+        if json {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                r#"{{"backend":"{}","max":{},"primes":{},"seconds":{},"primes_per_sec":{},"memory_bytes":{}}}"#,
+                name,
+                max,
+                count,
+                seconds,
+                rate,
+                sieve.heap_size()
+            )?;
+        } else {
+            writeln!(
+                out,
+                "{:<14}{:>12.3}{:>16.0}{:>14}",
+                name,
+                seconds,
+                rate,
+                format_bytes(sieve.heap_size())
+            )?;
+        }
+    }
+    if json {
+        writeln!(out, "]")?;
+    }
+    Ok(())
+}
+
+/// Format a number of bytes with a binary unit prefix, e.g. `"1.5 MiB"`.
+fn format_bytes(bytes: usize) -> String {
+    let mut value = bytes as f64;
+    for unit in &["B", "KiB", "MiB", "GiB"] {
+        if value < 1024.0 {
+            return format!("{:.1} {}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.1} TiB", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Run the binary with `args` (after its name), returning what it wrote to `path`
+    fn run_to_file(path: &Path, args: &[&str]) -> Vec<u8> {
+        let path = path.to_str().unwrap();
+        let mut full_args = vec!["prime_sieve", "-o", path];
+        full_args.extend_from_slice(args);
+        run(&cli().try_get_matches_from(full_args).unwrap()).unwrap();
+        let written = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        written
+    }
+
+    #[test]
+    fn factorizes() {
+        assert_eq!(prime_factors(0), Vec::<u64>::new());
+        assert_eq!(prime_factors(1), Vec::<u64>::new());
+        assert_eq!(prime_factors(1_000_000_007), vec![1_000_000_007]);
+        assert_eq!(
+            prime_factors(1_000_003 * 1_000_003),
+            vec![1_000_003, 1_000_003]
+        );
+        // The cofactor is prime and well above √n, so sieving stops at 3
+        assert_eq!(
+            prime_factors(12 * 1_000_000_007),
+            vec![2, 2, 3, 1_000_000_007]
+        );
+        assert_eq!(
+            prime_factors(u64::MAX),
+            vec![3, 5, 17, 257, 641, 65_537, 6_700_417]
+        );
+        for n in 1..=1000 {
+            assert_eq!(prime_factors(n).iter().product::<u64>(), n, "n {}", n);
+        }
+    }
+
+    #[test]
+    fn renders_json() {
+        let to_string = |write: &dyn Fn(&mut Vec<u8>) -> io::Result<()>| {
+            let mut out = Vec::new();
+            write(&mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            to_string(&|out| check(out, true, 97)),
+            "{\"number\":97,\"prime\":true}\n"
+        );
+        assert_eq!(to_string(&|out| list(out, true, 10)), "[2,3,5,7]\n");
+        assert_eq!(to_string(&|out| list(out, true, 1)), "[]\n");
+        assert_eq!(
+            to_string(&|out| count(out, true, 100)),
+            "{\"max\":100,\"count\":25}\n"
+        );
+        assert_eq!(
+            to_string(&|out| nth(out, true, 0)),
+            "{\"n\":0,\"prime\":null}\n"
+        );
+        assert_eq!(
+            to_string(&|out| gaps(out, true, false, 8)),
+            "[{\"prime\":2,\"gap\":1},{\"prime\":3,\"gap\":2},{\"prime\":5,\"gap\":2}]\n"
+        );
+        assert_eq!(
+            to_string(&|out| gaps(out, true, true, 2)),
+            "{\"max\":2,\"largest_gap\":null}\n"
+        );
+        assert_eq!(
+            to_string(&|out| factor(out, true, 12)),
+            "{\"number\":12,\"factors\":[2,2,3]}\n"
+        );
+        assert_eq!(
+            to_string(&|out| factor(out, true, 1)),
+            "{\"number\":1,\"factors\":[]}\n"
+        );
+
+        // `--json` and `--format json` are the same
+        let path = env::temp_dir().join(format!("prime_sieve_json_{}", process::id()));
+        let flag = run_to_file(&path, &["--json", "nth", "5"]);
+        let format = run_to_file(&path, &["--format", "json", "nth", "5"]);
+        assert_eq!(flag, b"{\"n\":5,\"prime\":11}\n");
+        assert_eq!(flag, format);
+    }
+
+    #[test]
+    fn writes_sieve_format() {
+        let path = env::temp_dir().join(format!("prime_sieve_table_{}", process::id()));
+        run(&cli()
+            .try_get_matches_from([
+                "prime_sieve",
+                "--format",
+                "sieve",
+                "-o",
+                path.to_str().unwrap(),
+                "list",
+                "100000",
+            ])
+            .unwrap())
+        .unwrap();
+        assert_eq!(Sieve::read_from(&path).unwrap(), Sieve::new(100_000));
+        fs::remove_file(&path).unwrap();
+    }
 }
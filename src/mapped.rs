@@ -0,0 +1,141 @@
+//! Full tables backed by a memory-mapped file, for sieves too big to comfortably keep in memory.
+//!
+//! The file is just scratch space in native byte order - use `Sieve::write_to()` to save a sieve
+//! for later.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem::size_of;
+use std::path::Path;
+use std::slice;
+
+use memmap2::MmapMut;
+
+use crate::bits::BitTable;
+use crate::{table_len, Sieve, Table};
+
+/// A run of words kept in a file mapped into memory.
+#[derive(Debug)]
+pub(crate) struct MappedWords {
+    file: File,
+    map: MmapMut,
+}
+
+impl MappedWords {
+    /// Create (or truncate) the file at `path` and map it with room for `len` words, all `value`.
+    pub(crate) fn create(path: &Path, len: usize, value: u64) -> io::Result<MappedWords> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        let mut words = MappedWords {
+            map: map(&file, 0)?,
+            file,
+        };
+        words.resize(len, value)?;
+        Ok(words)
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u64] {
+        if self.map.is_empty() {
+            return &[];
+        }
+        // SAFETY: mappings are page-aligned, their length is always a whole number of words and
+        // every bit pattern is a valid `u64`
+        unsafe { slice::from_raw_parts(self.map.as_ptr() as *const u64, self.len()) }
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u64] {
+        if self.map.is_empty() {
+            return &mut [];
+        }
+        let len = self.len();
+        // SAFETY: as for `as_slice`, and the mapping is borrowed mutably
+        unsafe { slice::from_raw_parts_mut(self.map.as_mut_ptr() as *mut u64, len) }
+    }
+
+    /// Change the number of words to `new_len`, setting any new ones to `value`.
+    pub(crate) fn resize(&mut self, new_len: usize, value: u64) -> io::Result<()> {
+        let old_len = self.len();
+        self.map.flush()?;
+        self.file.set_len((new_len * size_of::<u64>()) as u64)?;
+        self.map = map(&self.file, new_len)?;
+        if new_len > old_len {
+            for word in &mut self.as_mut_slice()[old_len..] {
+                *word = value;
+            }
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.map.len() / size_of::<u64>()
+    }
+}
+
+fn map(file: &File, len: usize) -> io::Result<MmapMut> {
+    // SAFETY: the file was just created for this sieve alone. Nothing else should touch it while
+    // it's mapped, and this mapping is the only way the sieve reads or writes it
+    unsafe {
+        memmap2::MmapOptions::new()
+            .len(len * size_of::<u64>())
+            .map_mut(file)
+    }
+}
+
+impl Sieve {
+    /// Create and populate a prime sieve with the maximum value `max`, keeping its table in a
+    /// memory-mapped file at `path` rather than in memory.
+    ///
+    /// The operating system pages the table in and out as needed, so this can build sieves larger
+    /// than the available RAM as long as there's room on disk (about a byte for every 30
+    /// numbers). Any existing file at `path` is overwritten.
+    ///
+    /// ```
+    /// let path = std::env::temp_dir().join("prime_sieve_doc_example.table");
+    /// let my_sieve = prime_sieve::Sieve::mapped(1_000_000, &path).unwrap();
+    ///
+    /// assert_eq!(my_sieve.lookup(999_983).unwrap(), true);
+    /// assert_eq!(my_sieve.heap_size(), 0);
+    /// # drop(my_sieve);
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn mapped<P: AsRef<Path>>(max: u64, path: P) -> io::Result<Sieve> {
+        let mut result = Sieve {
+            max,
+            table: Table::Full(BitTable::ones_mapped(table_len(max), path.as_ref())?),
+            filled: false,
+            fill_state: None,
+        };
+        result.fill();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn mapped_matches_in_memory() {
+        let path = env::temp_dir().join(format!("prime_sieve_test_{}.table", std::process::id()));
+        for &max in &[0, 1, 241, 1_000_003] {
+            let mut mapped = Sieve::mapped(max, &path).unwrap();
+            assert_eq!(
+                mapped.first_difference(&Sieve::new(max)),
+                None,
+                "max {}",
+                max
+            );
+
+            mapped.extend_to(max + 5_000);
+            let extended = Sieve::new(max + 5_000);
+            assert_eq!(mapped.first_difference(&extended), None, "max {}", max);
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
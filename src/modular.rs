@@ -0,0 +1,210 @@
+//! Modular arithmetic and divisibility on `u64`s, without overflowing along the way.
+
+use crate::{Sieve, SieveError};
+
+/// Compute `a * b % modulus`.
+///
+/// ```
+/// use prime_sieve::modular::mul_mod;
+///
+/// assert_eq!(mul_mod(u64::MAX, u64::MAX, 1_000_000_007), 114_944_269);
+/// ```
+pub fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
+}
+
+/// Compute `base.pow(exponent) % modulus` by repeated squaring.
+///
+/// ```
+/// use prime_sieve::modular::pow_mod;
+///
+/// assert_eq!(pow_mod(2, 10, 1000), 24);
+/// assert_eq!(pow_mod(3, 0, 1), 0);
+/// ```
+pub fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Compute the greatest common divisor of `a` and `b`, with `gcd(0, 0) == 0`.
+///
+/// ```
+/// use prime_sieve::modular::gcd;
+///
+/// assert_eq!(gcd(84, 36), 12);
+/// assert_eq!(gcd(0, 7), 7);
+/// ```
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    // Stein's binary algorithm, which only needs shifts and subtraction
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            core::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+/// Compute the least common multiple of `a` and `b`, which is `0` if either of them is.
+///
+/// Returns `None` if the result doesn't fit in a `u64`.
+///
+/// ```
+/// use prime_sieve::modular::lcm;
+///
+/// assert_eq!(lcm(4, 6), Some(12));
+/// assert_eq!(lcm(0, 6), Some(0));
+/// assert_eq!(lcm(u64::MAX, 2), None);
+/// ```
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Compute the Jacobi symbol `(a / n)`, which is `0`, `1` or `-1`.
+///
+/// Returns `None` unless `n` is odd (the symbol isn't defined otherwise).
+///
+/// ```
+/// use prime_sieve::modular::jacobi;
+///
+/// assert_eq!(jacobi(2, 15), Some(1));
+/// assert_eq!(jacobi(7, 15), Some(-1));
+/// assert_eq!(jacobi(5, 15), Some(0));
+/// assert_eq!(jacobi(1, 4), None);
+/// ```
+pub fn jacobi(a: u64, n: u64) -> Option<i8> {
+    if n.is_multiple_of(2) {
+        return None;
+    }
+    let mut a = a % n;
+    let mut n = n;
+    let mut result = 1;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                result = -result;
+            }
+        }
+        core::mem::swap(&mut a, &mut n);
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        a %= n;
+    }
+    Some(if n == 1 { result } else { 0 })
+}
+
+impl Sieve {
+    /// Determine whether `a` and `b` are coprime, i.e. share no prime factors.
+    ///
+    /// This is the same as `gcd(a, b) == 1`, so works on any sieve (filled or not) and for any
+    /// values, however large.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert!(my_sieve.coprime(8, 15));
+    /// assert!(!my_sieve.coprime(1_000_000_014, 12));
+    /// ```
+    pub fn coprime(&self, a: u64, b: u64) -> bool {
+        gcd(a, b) == 1
+    }
+
+    /// Compute the Legendre symbol `(a / p)`: `0` if `p` divides `a`, `1` if `a` is a quadratic
+    /// residue mod `p` and `-1` if it isn't. Every odd number is a residue mod `2`.
+    ///
+    /// Returns `Err(SieveError::NotPrime)` if the sieve shows that `p` isn't prime, as well as the
+    /// same errors as `Sieve::lookup()`.
+    ///
+    /// ```
+    /// use prime_sieve::{Sieve, SieveError};
+    ///
+    /// let my_sieve = Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.legendre(10, 13), Ok(1));
+    /// assert_eq!(my_sieve.legendre(5, 13), Ok(-1));
+    /// assert_eq!(my_sieve.legendre(2, 15), Err(SieveError::NotPrime { value: 15 }));
+    /// ```
+    pub fn legendre(&self, a: u64, p: u64) -> Result<i8, SieveError> {
+        if !self.lookup(p)? {
+            return Err(SieveError::NotPrime { value: p });
+        }
+        if p == 2 {
+            return Ok((a % 2) as i8);
+        }
+        Ok(jacobi(a, p).expect("odd primes always have a Jacobi symbol"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legendre_matches_euler_criterion() {
+        let sieve = Sieve::new(200);
+        for p in sieve.primes().skip(1) {
+            for a in 0..2 * p {
+                let euler = match pow_mod(a, (p - 1) / 2, p) {
+                    0 => 0,
+                    1 => 1,
+                    _ => -1,
+                };
+                assert_eq!(sieve.legendre(a, p), Ok(euler), "({} / {})", a, p);
+            }
+        }
+    }
+
+    #[test]
+    fn gcd_matches_euclid() {
+        fn euclid(a: u64, b: u64) -> u64 {
+            if b == 0 {
+                a
+            } else {
+                euclid(b, a % b)
+            }
+        }
+        for a in 0..200 {
+            for b in 0..200 {
+                assert_eq!(gcd(a, b), euclid(a, b), "gcd({}, {})", a, b);
+                if a > 0 && b > 0 {
+                    assert_eq!(lcm(a, b), Some(a * b / euclid(a, b)));
+                }
+            }
+        }
+        assert_eq!(gcd(u64::MAX, u64::MAX - 1), 1);
+        assert_eq!(gcd(1 << 63, 1 << 40), 1 << 40);
+    }
+
+    #[test]
+    fn jacobi_is_multiplicative_in_n() {
+        for a in 0..50 {
+            for m in (1..40).step_by(2) {
+                for n in (1..40).step_by(2) {
+                    let product = jacobi(a, m).unwrap() * jacobi(a, n).unwrap();
+                    assert_eq!(jacobi(a, m * n), Some(product));
+                }
+            }
+        }
+    }
+}
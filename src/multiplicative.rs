@@ -0,0 +1,381 @@
+//! Sieves for multiplicative functions, which tabulate a function for every number up to a max
+//! in one pass, plus per-value versions on `Sieve` that work by factorization.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Mul;
+
+use crate::{dense_table_len, linear, sqrt_floor, Sieve, SieveError};
+
+/// Compute Euler's totient `φ(n)` for every `n <= max`, with `φ(0) = 0`.
+///
+/// Each value is worked out from a smaller one using smallest prime factors from the linear
+/// sieve, so this takes `O(max)` time.
+///
+/// ```
+/// let phi = prime_sieve::multiplicative::totient_sieve(10);
+/// assert_eq!(phi, vec![0, 1, 1, 2, 2, 4, 2, 6, 4, 6, 4]);
+/// ```
+pub fn totient_sieve(max: u64) -> Vec<u64> {
+    let smallest_factors = linear::smallest_factors(max);
+    let mut phi: Vec<u64> = vec![0; smallest_factors.len()];
+    for n in 1..phi.len() {
+        phi[n] = match smallest_factors[n] {
+            0 if n == 1 => 1,
+            0 => n as u64 - 1,
+            p => {
+                // `φ(pm)` is `φ(m) · p` if `p` divides `m`, or `φ(m) · (p - 1)` otherwise
+                let (p, rest) = (p as usize, n / p as usize);
+                let factor = if rest.is_multiple_of(p) { p } else { p - 1 };
+                phi[rest] * factor as u64
+            }
+        };
+    }
+    phi
+}
+
+/// Compute the Möbius function `μ(n)` for every `n <= max`, with `μ(0) = 0`.
+///
+/// `μ(n)` is `0` if `n` has a square factor, and otherwise `1` or `-1` depending on whether it has
+/// an even or odd number of prime factors.
+///
+/// ```
+/// let mu = prime_sieve::multiplicative::mobius_sieve(10);
+/// assert_eq!(mu, vec![0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1]);
+/// ```
+pub fn mobius_sieve(max: u64) -> Vec<i8> {
+    let mut mu = vec![1; dense_table_len(max)];
+    mu[0] = 0;
+    for p in Sieve::new(max).primes() {
+        for multiple in (p..=max).step_by(p as usize) {
+            mu[multiple as usize] *= -1;
+        }
+        if let Some(square) = p.checked_mul(p) {
+            for multiple in (square..=max).step_by(square as usize) {
+                mu[multiple as usize] = 0;
+            }
+        }
+    }
+    mu
+}
+
+/// Compute the Liouville function `λ(n)` for every `n <= max`, with `λ(0) = 0`.
+///
+/// `λ(n)` is `1` or `-1` depending on whether `n` has an even or odd number of prime factors,
+/// counted with multiplicity. It agrees with `μ(n)` wherever that isn't `0`.
+///
+/// ```
+/// let lambda = prime_sieve::multiplicative::liouville_sieve(10);
+/// assert_eq!(lambda, vec![0, 1, -1, -1, 1, -1, 1, -1, -1, 1, 1]);
+/// ```
+pub fn liouville_sieve(max: u64) -> Vec<i8> {
+    let mut lambda = vec![1; dense_table_len(max)];
+    lambda[0] = 0;
+    for p in Sieve::new(max).primes() {
+        // Flip once for each power of `p` dividing the multiple
+        let mut power = Some(p);
+        while let Some(step) = power.filter(|&step| step <= max) {
+            for multiple in (step..=max).step_by(step as usize) {
+                lambda[multiple as usize] *= -1;
+            }
+            power = step.checked_mul(p);
+        }
+    }
+    lambda
+}
+
+/// Determine which numbers up to `max` are squarefree, i.e. not divisible by the square of any
+/// prime. `0` isn't squarefree, since every square divides it.
+///
+/// This marks the multiples of `p²` for each prime `p <= √max`, which is far quicker than checking
+/// numbers one at a time with `Sieve::is_squarefree()` when there are a lot of them.
+///
+/// ```
+/// let squarefree = prime_sieve::multiplicative::squarefree_sieve(10);
+/// let expected = [false, true, true, true, false, true, true, true, false, false, true];
+/// assert_eq!(squarefree, expected);
+/// ```
+pub fn squarefree_sieve(max: u64) -> Vec<bool> {
+    let mut squarefree = vec![true; dense_table_len(max)];
+    squarefree[0] = false;
+    for p in Sieve::new(sqrt_floor(max)).primes() {
+        let square = p * p;
+        for multiple in (square..=max).step_by(square as usize) {
+            squarefree[multiple as usize] = false;
+        }
+    }
+    squarefree
+}
+
+/// Compute the number of divisors `d(n)` of every `n <= max`, with `d(0) = 0`.
+///
+/// Like `totient_sieve()`, this takes a single `O(max)` pass using smallest prime factors from
+/// the linear sieve.
+///
+/// ```
+/// let d = prime_sieve::multiplicative::divisor_count_sieve(10);
+/// assert_eq!(d, vec![0, 1, 2, 2, 3, 2, 4, 2, 4, 3, 4]);
+/// ```
+pub fn divisor_count_sieve(max: u64) -> Vec<u32> {
+    // `d(pᵉ) = e + 1`
+    multiplicative_sieve(max, 0, 1, |_, previous| previous + 1)
+}
+
+/// Compute the sum of the divisors `σ(n)` of every `n <= max`, with `σ(0) = 0`.
+///
+/// Like `totient_sieve()`, this takes a single `O(max)` pass using smallest prime factors from
+/// the linear sieve.
+///
+/// ```
+/// let sigma = prime_sieve::multiplicative::divisor_sum_sieve(10);
+/// assert_eq!(sigma, vec![0, 1, 3, 4, 7, 6, 12, 8, 15, 13, 18]);
+/// ```
+pub fn divisor_sum_sieve(max: u64) -> Vec<u64> {
+    // `σ(pᵉ) = 1 + p + ... + pᵉ = p · σ(pᵉ⁻¹) + 1`
+    multiplicative_sieve(max, 0, 1, |p, previous| previous * p + 1)
+}
+
+// Tabulate a multiplicative function `f` for every `n <= max`, given `f(0)`, `f(1)` and
+// `f(pᵉ) = next_power(p, f(pᵉ⁻¹))`. Each `n` splits into the largest power of its smallest prime
+// factor and what's left, which are coprime, so `f(n)` is the product of two earlier values.
+fn multiplicative_sieve<T, F>(max: u64, zero: T, one: T, mut next_power: F) -> Vec<T>
+where
+    T: Copy + Mul<Output = T>,
+    F: FnMut(u64, T) -> T,
+{
+    let smallest_factors = linear::smallest_factors(max);
+    let mut values = vec![zero; smallest_factors.len()];
+    // The largest power of each number's smallest prime factor that divides it
+    let mut powers = vec![1usize; smallest_factors.len()];
+    if let Some(value) = values.get_mut(1) {
+        *value = one;
+    }
+    for n in 2..values.len() {
+        let p = match smallest_factors[n] {
+            0 => n,
+            p => p as usize,
+        };
+        let rest = n / p;
+        powers[n] = if rest.is_multiple_of(p) {
+            powers[rest] * p
+        } else {
+            p
+        };
+        let power = powers[n];
+        values[n] = if power == n {
+            next_power(p as u64, values[rest])
+        } else {
+            values[power] * values[n / power]
+        };
+    }
+    values
+}
+
+impl Sieve {
+    /// Compute Euler's totient `φ(n)`, the count of numbers up to `n` that are coprime to it.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.totient(36).unwrap(), 12);
+    /// assert_eq!(my_sieve.totient(9_973).unwrap(), 9_972);
+    /// ```
+    pub fn totient(&self, n: u64) -> Result<u64, SieveError> {
+        let mut result = n;
+        let mut last = 0;
+        for p in self.factorize(n)? {
+            if p != last {
+                result -= result / p;
+                last = p;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Compute the Möbius function `μ(n)` - see `multiplicative::mobius_sieve()`.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.mobius(30).unwrap(), -1);
+    /// assert_eq!(my_sieve.mobius(12).unwrap(), 0);
+    /// ```
+    pub fn mobius(&self, n: u64) -> Result<i8, SieveError> {
+        let factors = self.factorize(n)?;
+        if factors.windows(2).any(|pair| pair[0] == pair[1]) {
+            Ok(0)
+        } else if factors.len() % 2 == 0 {
+            Ok(1)
+        } else {
+            Ok(-1)
+        }
+    }
+
+    /// Compute the Liouville function `λ(n)` - see `multiplicative::liouville_sieve()`.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.liouville(12).unwrap(), -1);
+    /// assert_eq!(my_sieve.liouville(36).unwrap(), 1);
+    /// ```
+    pub fn liouville(&self, n: u64) -> Result<i8, SieveError> {
+        if self.factorize(n)?.len() % 2 == 0 {
+            Ok(1)
+        } else {
+            Ok(-1)
+        }
+    }
+
+    /// Determine whether `n` is squarefree - see `multiplicative::squarefree_sieve()`, which is
+    /// much quicker for checking every number in a range.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_squarefree(30).unwrap(), true);
+    /// assert_eq!(my_sieve.is_squarefree(97 * 97).unwrap(), false);
+    /// ```
+    pub fn is_squarefree(&self, n: u64) -> Result<bool, SieveError> {
+        Ok(self.mobius(n)? != 0)
+    }
+
+    /// Compute the radical `rad(n)`, the product of the distinct primes dividing `n` (so
+    /// `rad(1) = 1`).
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.radical(360).unwrap(), 30);
+    /// assert_eq!(my_sieve.radical(97).unwrap(), 97);
+    /// ```
+    pub fn radical(&self, n: u64) -> Result<u64, SieveError> {
+        Ok(self.factorization(n)?.radical())
+    }
+
+    /// Count the divisors `d(n)` of `n`, including `1` and `n` itself.
+    ///
+    /// Returns the same errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.divisor_count(360).unwrap(), 24);
+    /// ```
+    pub fn divisor_count(&self, n: u64) -> Result<u64, SieveError> {
+        Ok(self.factorization(n)?.num_divisors())
+    }
+
+    /// Sum the divisors `σ(n)` of `n`, including `1` and `n` itself.
+    ///
+    /// Returns a `u128`, since `σ(n)` can be several times larger than `n`. Returns the same
+    /// errors as `Sieve::factorize()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    /// assert_eq!(my_sieve.divisor_sum(360).unwrap(), 1170);
+    /// ```
+    pub fn divisor_sum(&self, n: u64) -> Result<u128, SieveError> {
+        Ok(self.factorization(n)?.divisor_sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totient_sieve_matches_factorization() {
+        let phi = totient_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            assert_eq!(phi[n as usize], sieve.totient(n).unwrap(), "{}", n);
+        }
+        assert_eq!(totient_sieve(0), vec![0]);
+        assert_eq!(sieve.totient(0), Err(SieveError::Zero));
+    }
+
+    #[test]
+    fn mobius_sieve_matches_factorization() {
+        let mu = mobius_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            assert_eq!(mu[n as usize], sieve.mobius(n).unwrap(), "{}", n);
+        }
+        assert_eq!(mobius_sieve(1), vec![0, 1]);
+    }
+
+    #[test]
+    fn liouville_sieve_matches_factorization() {
+        let lambda = liouville_sieve(10_000);
+        let mu = mobius_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            assert_eq!(lambda[n as usize], sieve.liouville(n).unwrap(), "{}", n);
+            assert!(mu[n as usize] == 0 || mu[n as usize] == lambda[n as usize]);
+        }
+        // The summatory Liouville function is negative this far
+        assert_eq!(lambda.iter().map(|&x| i64::from(x)).sum::<i64>(), -94);
+        assert_eq!(liouville_sieve(0), vec![0]);
+        assert_eq!(sieve.liouville(1 << 63).unwrap(), -1);
+    }
+
+    #[test]
+    fn squarefree_sieve_matches_factorization() {
+        let squarefree = squarefree_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            assert_eq!(
+                squarefree[n as usize],
+                sieve.is_squarefree(n).unwrap(),
+                "{}",
+                n
+            );
+        }
+        // 6/π² of all numbers are squarefree
+        assert_eq!(squarefree.iter().filter(|&&x| x).count(), 6083);
+        assert_eq!(squarefree_sieve(0), vec![false]);
+        assert_eq!(squarefree_sieve(4), vec![false, true, true, true, false]);
+    }
+
+    #[test]
+    fn radical_is_squarefree_kernel() {
+        let squarefree = squarefree_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            let radical = sieve.radical(n).unwrap();
+            assert!(n.is_multiple_of(radical) && squarefree[radical as usize]);
+            assert_eq!(radical == n, squarefree[n as usize], "{}", n);
+        }
+        assert_eq!(sieve.radical(1), Ok(1));
+        assert_eq!(sieve.radical(0), Err(SieveError::Zero));
+    }
+
+    #[test]
+    fn divisor_sieves_match_factorization() {
+        let counts = divisor_count_sieve(10_000);
+        let sums = divisor_sum_sieve(10_000);
+        let sieve = Sieve::new(100);
+        for n in 1..=10_000 {
+            assert_eq!(
+                u64::from(counts[n as usize]),
+                sieve.divisor_count(n).unwrap()
+            );
+            assert_eq!(u128::from(sums[n as usize]), sieve.divisor_sum(n).unwrap());
+        }
+        assert_eq!(divisor_count_sieve(0), vec![0]);
+        assert_eq!(divisor_sum_sieve(1), vec![0, 1]);
+        let large = Sieve::new(1 << 16);
+        assert_eq!(large.divisor_count(1 << 63).unwrap(), 64);
+        assert_eq!(large.divisor_sum(1 << 63).unwrap(), (1 << 64) - 1);
+    }
+}
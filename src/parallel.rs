@@ -0,0 +1,111 @@
+//! Multi-threaded population of full sieves and batch queries, using rayon.
+
+use rayon::prelude::*;
+
+use crate::{sieve_chunk, sqrt_floor, Sieve, SieveError, Table, CHUNK_WORDS};
+
+impl Sieve {
+    /// Populate an unfilled sieve like `Sieve::fill()`, but split the work across all of rayon's
+    /// threads. The resulting table is identical.
+    ///
+    /// Has no effect on already-filled sieves.
+    ///
+    /// ```
+    /// let mut my_sieve = prime_sieve::Sieve::unfilled(1_000_000);
+    /// my_sieve.fill_parallel();
+    ///
+    /// assert_eq!(my_sieve.count_primes(), 78_498);
+    /// ```
+    pub fn fill_parallel(&mut self) {
+        if self.filled {
+            return;
+        }
+        let max = self.max;
+        match &mut self.table {
+            Table::Full(table) => {
+                let base_primes: Vec<u64> = Sieve::new(sqrt_floor(max)).primes().collect();
+                table
+                    .words_mut()
+                    .par_chunks_mut(CHUNK_WORDS)
+                    .enumerate()
+                    .for_each(|(i, chunk)| {
+                        sieve_chunk(chunk, i, &base_primes, max);
+                    });
+                self.filled = true;
+                self.fill_state = None;
+            }
+            Table::Segmented { .. } => self.fill(),
+        }
+    }
+
+    /// Like `Sieve::filter()`, but split the lookups across all of rayon's threads. The primes
+    /// come out in their original order.
+    ///
+    /// If more than one of `target`'s elements is outside the bounds of this sieve, which one the
+    /// `Err(SieveError::OutOfBounds)` reports isn't specified.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.filter_par(vec![7, 4, 3, 97]).unwrap(), vec![7, 3, 97]);
+    /// assert_eq!(my_sieve.filter_par(90..100).unwrap(), vec![97]);
+    /// assert!(my_sieve.filter_par(90..200).is_err());
+    /// ```
+    pub fn filter_par<I>(&self, target: I) -> Result<Vec<u64>, SieveError>
+    where
+        I: IntoParallelIterator<Item = u64>,
+    {
+        target
+            .into_par_iter()
+            .filter_map(|n| match self.lookup(n) {
+                Ok(true) => Some(Ok(n)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_matches_serial() {
+        for &max in &[0, 1, 2, 239, 240, 241, 5_000_000, 5_000_011] {
+            let mut parallel = Sieve::unfilled(max);
+            parallel.fill_parallel();
+            assert_eq!(
+                parallel.first_difference(&Sieve::new(max)),
+                None,
+                "max {}",
+                max
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_filter_matches_serial() {
+        let sieve = Sieve::new(1_000_000);
+        let targets: Vec<u64> = (0..1_000_000).rev().step_by(3).collect();
+        assert_eq!(
+            sieve.filter_par(targets.clone()),
+            sieve.filter(targets.clone())
+        );
+        assert_eq!(
+            sieve.filter_par(targets.par_iter().copied()),
+            sieve.filter(targets)
+        );
+        assert_eq!(
+            sieve.filter_par(vec![2, 1_000_001]),
+            Err(SieveError::OutOfBounds {
+                value: 1_000_001,
+                max: 1_000_000
+            })
+        );
+        assert_eq!(
+            Sieve::unfilled(10).filter_par(0..5),
+            Err(SieveError::NotFilled)
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Multi-threaded, streaming iteration over the primes of a sieve, using crossbeam channels.
+//!
+//! Each worker thread gets a copy of the base primes and sieves every `threads`th segment in
+//! turn, sending the primes it finds down its own bounded channel. Reading the channels
+//! round-robin puts the primes back in order, and the bound on each channel keeps memory use
+//! fixed however large the sieve is.
+
+use std::panic;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::{bounded, Receiver};
+
+use crate::{segmented, sqrt_floor, Sieve, Table};
+
+/// How many numbers each segment covers, unless the sieve is segmented with a size of its own
+const SEGMENT_SIZE: u64 = 1 << 18;
+
+/// How many finished segments each worker can get ahead by
+const SEGMENTS_AHEAD: usize = 2;
+
+/// Iterator over the primes in a sieve in ascending order, sieved by a pool of worker threads -
+/// see `Sieve::primes_pipelined()`.
+#[derive(Debug)]
+pub struct PipelinedPrimes {
+    receivers: Vec<Receiver<Vec<u64>>>,
+    // Taken once they've been joined
+    workers: Vec<Option<JoinHandle<()>>>,
+    next_worker: usize,
+    current: std::vec::IntoIter<u64>,
+}
+
+impl Sieve {
+    /// Iterate over the primes in this sieve like `Sieve::primes()`, but sieve them a segment at a
+    /// time across `threads` worker threads.
+    ///
+    /// Only the primes up to `√max` are needed up front, so this works just as well for
+    /// segmented sieves far too large to keep a full table for. Yields nothing if the sieve is
+    /// unfilled. Dropping the iterator stops the workers.
+    ///
+    /// Panics if `threads` is `0`. If a worker thread panics, the iterator passes the panic on
+    /// rather than ending early.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::segmented(10_000_000, 1 << 16);
+    ///
+    /// assert_eq!(my_sieve.primes_pipelined(4).count(), 664_579);
+    /// ```
+    pub fn primes_pipelined(&self, threads: usize) -> PipelinedPrimes {
+        assert!(threads > 0, "Thread count must be non-zero");
+        let mut result = PipelinedPrimes {
+            receivers: Vec::new(),
+            workers: Vec::new(),
+            next_worker: 0,
+            current: Vec::new().into_iter(),
+        };
+        if !self.filled {
+            return result;
+        }
+        let (base_primes, segment_size) = match &self.table {
+            Table::Full(_) => {
+                let bound = sqrt_floor(self.max);
+                (
+                    self.primes().take_while(|&p| p <= bound).collect(),
+                    SEGMENT_SIZE,
+                )
+            }
+            Table::Segmented {
+                segment_size,
+                base_primes,
+            } => (base_primes.clone(), *segment_size),
+        };
+        let base_primes: Arc<Vec<u64>> = Arc::new(base_primes);
+        let max = self.max;
+
+        for first_segment in 0..threads as u64 {
+            let (sender, receiver) = bounded(SEGMENTS_AHEAD);
+            let base_primes = Arc::clone(&base_primes);
+            result.receivers.push(receiver);
+            result.workers.push(Some(thread::spawn(move || {
+                let mut flags = Vec::new();
+                for segment in (first_segment..).step_by(threads) {
+                    let low = match segment.checked_mul(segment_size) {
+                        Some(low) if low <= max => low,
+                        _ => return,
+                    };
+                    let high = low.saturating_add(segment_size - 1).min(max);
+                    flags.resize((high - low + 1) as usize, true);
+                    segmented::sieve_segment(&base_primes, low, &mut flags);
+                    let primes = (low..=high).zip(&flags).filter(|&(_, &prime)| prime);
+                    // The iterator's been dropped if nobody's listening
+                    if sender.send(primes.map(|(n, _)| n).collect()).is_err() {
+                        return;
+                    }
+                }
+            })));
+        }
+        result
+    }
+}
+
+impl Iterator for PipelinedPrimes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            if let Some(prime) = self.current.next() {
+                return Some(prime);
+            }
+            let receiver = self.receivers.get(self.next_worker)?;
+            match receiver.recv() {
+                Ok(primes) => self.current = primes.into_iter(),
+                Err(_) => {
+                    // Segments arrive round-robin, so once a worker that finished normally hangs
+                    // up, every segment after it is past the max too. One that panicked instead
+                    // would cut the primes short, so pass its panic on.
+                    let worker = self.workers[self.next_worker].take();
+                    self.receivers.clear();
+                    if let Some(Err(payload)) = worker.map(JoinHandle::join) {
+                        panic::resume_unwind(payload);
+                    }
+                    return None;
+                }
+            }
+            self.next_worker = (self.next_worker + 1) % self.receivers.len();
+        }
+    }
+}
+
+impl Drop for PipelinedPrimes {
+    fn drop(&mut self) {
+        // Hang up first so that workers blocked on a full channel give up
+        self.receivers.clear();
+        for worker in self.workers.drain(..).flatten() {
+            // Don't hide a worker's panic, unless this is already unwinding from one
+            if let Err(payload) = worker.join() {
+                if !thread::panicking() {
+                    panic::resume_unwind(payload);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pipelined_matches_serial() {
+        for &(max, threads) in &[(0, 1), (1, 3), (1_000_000, 1), (2_000_003, 3)] {
+            let full = Sieve::new(max);
+            let expected: Vec<u64> = full.primes().collect();
+            assert_eq!(full.primes_pipelined(threads).collect::<Vec<_>>(), expected);
+            let segmented = Sieve::segmented(max, 1000);
+            assert_eq!(
+                segmented.primes_pipelined(threads).collect::<Vec<_>>(),
+                expected
+            );
+        }
+        assert_eq!(Sieve::unfilled(100).primes_pipelined(2).next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "worker failed")]
+    fn passes_on_worker_panics() {
+        let (sender, receiver) = bounded(1);
+        let worker = thread::spawn(move || {
+            sender.send(vec![2, 3]).unwrap();
+            panic!("worker failed");
+        });
+        let primes = PipelinedPrimes {
+            receivers: vec![receiver],
+            workers: vec![Some(worker)],
+            next_worker: 0,
+            current: Vec::new().into_iter(),
+        };
+        // Ending after 3 would silently lose the rest of the primes
+        primes.for_each(drop);
+    }
+
+    #[test]
+    fn dropping_early_stops_workers() {
+        let sieve = Sieve::segmented(1 << 40, 1 << 10);
+        assert_eq!(sieve.primes_pipelined(4).nth(3), Some(7));
+    }
+}
@@ -0,0 +1,412 @@
+//! Primality tests for single values, for numbers beyond the reach of a sieve.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::modular::{jacobi, mul_mod, pow_mod};
+use crate::{FactorSieve, Sieve, Sieve32, SieveError};
+
+/// The first 12 primes, which as Miller-Rabin bases are enough to be exact for every `u64`.
+const MILLER_RABIN_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Determine whether `n` is prime using a deterministic Miller-Rabin test.
+///
+/// Uses enough bases that the result is exact for every `u64`, not just probably correct.
+///
+/// ```
+/// use prime_sieve::primality::miller_rabin;
+///
+/// assert!(miller_rabin(18_446_744_073_709_551_557));
+/// assert!(!miller_rabin(3_215_031_751));
+/// ```
+pub fn miller_rabin(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &MILLER_RABIN_BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    MILLER_RABIN_BASES
+        .iter()
+        .all(|&base| strong_fermat(n, base))
+}
+
+/// Determine whether `n` is prime using the Baillie-PSW test: a strong Fermat test to base 2
+/// followed by a strong Lucas test.
+///
+/// No composite is known to pass both halves, and there are none below 2^64, so the result is
+/// exact for every `u64`. It's also quicker than `miller_rabin()`, especially for primes, which
+/// makes it the better choice for single values outside a sieve.
+///
+/// ```
+/// use prime_sieve::primality::baillie_psw;
+///
+/// assert!(baillie_psw(18_446_744_073_709_551_557));
+/// // A strong pseudoprime to base 2, caught by the Lucas half
+/// assert!(!baillie_psw(3_215_031_751));
+/// ```
+pub fn baillie_psw(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &MILLER_RABIN_BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    strong_fermat(n, 2) && strong_lucas(n)
+}
+
+// Whether odd `n` is a strong probable prime to `base`
+fn strong_fermat(n: u64, base: u64) -> bool {
+    let shift = (n - 1).trailing_zeros();
+    let odd_part = (n - 1) >> shift;
+    let mut x = pow_mod(base, odd_part, n);
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..shift {
+        x = mul_mod(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+// Whether odd `n` with no factors below 41 is a strong Lucas probable prime, with the parameters
+// `P = 1` and `Q = (1 - D) / 4` for the first `D` in 5, -7, 9, -11, ... with `(D/n) = -1`
+fn strong_lucas(n: u64) -> bool {
+    // No such `D` exists for squares
+    let root = n.isqrt();
+    if root * root == n {
+        return false;
+    }
+    let mut d: i64 = 5;
+    let residue = |value: i64| {
+        let magnitude = value.unsigned_abs() % n;
+        if value < 0 && magnitude != 0 {
+            n - magnitude
+        } else {
+            magnitude
+        }
+    };
+    loop {
+        match jacobi(residue(d), n) {
+            Some(-1) => break,
+            // `n` has no factors as small as `|D|`, so this means `|D|` shares one with `n`
+            Some(0) => return false,
+            _ => d = if d > 0 { -d - 2 } else { -d + 2 },
+        }
+    }
+    let (d_mod, q) = (residue(d), residue((1 - d) / 4));
+
+    let add = |a: u64, b: u64| ((u128::from(a) + u128::from(b)) % u128::from(n)) as u64;
+    let sub = |a: u64, b: u64| add(a, n - b);
+    let halve = |a: u64| {
+        if a.is_multiple_of(2) {
+            a / 2
+        } else {
+            ((u128::from(a) + u128::from(n)) / 2) as u64
+        }
+    };
+
+    // Walk the bits of the odd part of `n + 1` from the top, keeping `U_k`, `V_k` and `Q^k`
+    let shift = (n + 1).trailing_zeros();
+    let odd_part = (n + 1) >> shift;
+    let (mut u, mut v, mut q_k) = (1, 1, q);
+    for bit in (0..63 - odd_part.leading_zeros()).rev() {
+        // Doubling: `U_2k = U_k V_k`, `V_2k = V_k² - 2Q^k`
+        u = mul_mod(u, v, n);
+        v = sub(mul_mod(v, v, n), add(q_k, q_k));
+        q_k = mul_mod(q_k, q_k, n);
+        if odd_part >> bit & 1 == 1 {
+            // Incrementing: `U_k+1 = (U_k + V_k) / 2`, `V_k+1 = (D U_k + V_k) / 2`
+            let next_u = halve(add(u, v));
+            v = halve(add(mul_mod(d_mod, u, n), v));
+            u = next_u;
+            q_k = mul_mod(q_k, q, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..shift {
+        v = sub(mul_mod(v, v, n), add(q_k, q_k));
+        if v == 0 {
+            return true;
+        }
+        q_k = mul_mod(q_k, q_k, n);
+    }
+    false
+}
+
+/// Something that can say whether a number is prime, so code can be generic over how that's
+/// worked out.
+///
+/// Sieves answer from their tables where they can and fall back to `baillie_psw()` elsewhere, so
+/// every implementation gives an exact answer for every `u64`.
+///
+/// ```
+/// use prime_sieve::primality::{MillerRabin, PrimalityTest};
+/// use prime_sieve::Sieve;
+///
+/// fn count_primes<T: PrimalityTest>(tester: &T, max: u64) -> usize {
+///     (0..=max).filter(|&n| tester.is_prime(n)).count()
+/// }
+///
+/// assert_eq!(count_primes(&Sieve::new(1000), 1000), 168);
+/// assert_eq!(count_primes(&MillerRabin, 1000), 168);
+/// ```
+pub trait PrimalityTest {
+    /// Determine whether `n` is prime.
+    fn is_prime(&self, n: u64) -> bool;
+}
+
+/// The deterministic Miller-Rabin test from `miller_rabin()`, as a `PrimalityTest`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MillerRabin;
+
+impl PrimalityTest for MillerRabin {
+    fn is_prime(&self, n: u64) -> bool {
+        miller_rabin(n)
+    }
+}
+
+/// The Baillie-PSW test from `baillie_psw()`, as a `PrimalityTest`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BailliePsw;
+
+impl PrimalityTest for BailliePsw {
+    fn is_prime(&self, n: u64) -> bool {
+        baillie_psw(n)
+    }
+}
+
+impl PrimalityTest for Sieve {
+    fn is_prime(&self, n: u64) -> bool {
+        self.lookup_or_test(n)
+    }
+}
+
+impl PrimalityTest for Sieve32 {
+    fn is_prime(&self, n: u64) -> bool {
+        self.as_sieve().lookup_or_test(n)
+    }
+}
+
+impl PrimalityTest for FactorSieve {
+    fn is_prime(&self, n: u64) -> bool {
+        self.lookup(n).unwrap_or_else(|_| baillie_psw(n))
+    }
+}
+
+impl<T: PrimalityTest + ?Sized> PrimalityTest for &T {
+    fn is_prime(&self, n: u64) -> bool {
+        (**self).is_prime(n)
+    }
+}
+
+impl Sieve {
+    /// Determine whether the Mersenne number `2^p - 1` is prime, using the Lucas-Lehmer test.
+    ///
+    /// The sieve only has to cover `p` itself, so this can test numbers far too large to sieve.
+    /// It takes about `p³ / 4096` word multiplications, so exponents in the thousands are quick.
+    ///
+    /// Returns the same errors as `Sieve::lookup(p)`. If `p` isn't prime, neither is `2^p - 1`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    ///
+    /// assert_eq!(my_sieve.lucas_lehmer(521), Ok(true));
+    /// assert_eq!(my_sieve.lucas_lehmer(523), Ok(false));
+    /// ```
+    pub fn lucas_lehmer(&self, p: u32) -> Result<bool, SieveError> {
+        if !self.lookup(u64::from(p))? {
+            return Ok(false);
+        }
+        if p == 2 {
+            return Ok(true);
+        }
+        // Work on little-endian words, always keeping `s` reduced below `2^p - 1`
+        let words = (p as usize).div_ceil(64);
+        let mut s = vec![0; words];
+        s[0] = 4;
+        for _ in 0..p - 2 {
+            s = mersenne_reduce(square(&s), p);
+            subtract_two(&mut s, p);
+        }
+        Ok(s.iter().all(|&word| word == 0))
+    }
+}
+
+// Square a little-endian multi-word number
+fn square(x: &[u64]) -> Vec<u64> {
+    let mut result = vec![0; 2 * x.len()];
+    for (i, &a) in x.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &b) in x.iter().enumerate() {
+            let t = u128::from(a) * u128::from(b) + u128::from(result[i + j]) + carry;
+            result[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        result[i + x.len()] = carry as u64;
+    }
+    result
+}
+
+// Reduce a little-endian multi-word number mod `2^p - 1` by repeatedly adding the bits above `p`
+// onto the ones below it (since `2^p ≡ 1`), returning exactly enough words to hold `p` bits
+fn mersenne_reduce(mut value: Vec<u64>, p: u32) -> Vec<u64> {
+    let words = (p as usize).div_ceil(64);
+    let (word_shift, bit_shift) = ((p / 64) as usize, p % 64);
+    loop {
+        let mut high: Vec<u64> = (word_shift..value.len())
+            .map(|i| {
+                let next = value.get(i + 1).copied().unwrap_or(0);
+                if bit_shift == 0 {
+                    value[i]
+                } else {
+                    value[i] >> bit_shift | next << (64 - bit_shift)
+                }
+            })
+            .collect();
+        if high.iter().all(|&word| word == 0) {
+            break;
+        }
+        value.truncate(words);
+        if bit_shift != 0 {
+            value[words - 1] &= (1 << bit_shift) - 1;
+        }
+        value.resize(words.max(high.len()) + 1, 0);
+        high.resize(value.len(), 0);
+        let mut carry = false;
+        for (word, &add) in value.iter_mut().zip(&high) {
+            let (sum, overflow_a) = word.overflowing_add(add);
+            let (sum, overflow_b) = sum.overflowing_add(u64::from(carry));
+            *word = sum;
+            carry = overflow_a || overflow_b;
+        }
+    }
+    value.resize(words, 0);
+    // `2^p - 1` itself is congruent to `0`
+    let top_mask = if bit_shift == 0 {
+        u64::MAX
+    } else {
+        (1 << bit_shift) - 1
+    };
+    let is_modulus =
+        value[..words - 1].iter().all(|&word| word == u64::MAX) && value[words - 1] == top_mask;
+    if is_modulus {
+        value.iter_mut().for_each(|word| *word = 0);
+    }
+    value
+}
+
+// Subtract 2 from a number reduced mod `2^p - 1`, wrapping around below zero. Needs `p >= 3`.
+fn subtract_two(value: &mut [u64], p: u32) {
+    let low = value[0];
+    if value[1..].iter().all(|&word| word == 0) && low < 2 {
+        // `low - 2 + 2^p - 1`, where the low word of the modulus has at least 3 bits set
+        value.iter_mut().for_each(|word| *word = u64::MAX);
+        if !p.is_multiple_of(64) {
+            value[value.len() - 1] = (1 << (p % 64)) - 1;
+        }
+        value[0] -= 2 - low;
+        return;
+    }
+    let mut borrow = 2;
+    for word in value.iter_mut() {
+        let (difference, underflow) = word.overflowing_sub(borrow);
+        *word = difference;
+        if !underflow {
+            break;
+        }
+        borrow = 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sieve;
+
+    #[test]
+    fn miller_rabin_matches_sieve() {
+        let sieve = Sieve::new(100_000);
+        for n in 0..=100_000 {
+            assert_eq!(miller_rabin(n), sieve.lookup(n).unwrap(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn primality_tests_agree() {
+        let testers: [&dyn PrimalityTest; 6] = [
+            &MillerRabin,
+            &BailliePsw,
+            &Sieve::new(1000),
+            &Sieve::segmented(1000, 64),
+            &Sieve32::new(1000),
+            &FactorSieve::new(1000),
+        ];
+        for n in (0..2000).chain(u64::MAX - 100..=u64::MAX) {
+            let expected = miller_rabin(n);
+            for tester in &testers {
+                assert_eq!(tester.is_prime(n), expected, "n {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn miller_rabin_strong_pseudoprimes() {
+        // Strong pseudoprimes to every base up to 7 and up to 23 respectively
+        assert!(!miller_rabin(3_215_031_751));
+        assert!(!miller_rabin(3_825_123_056_546_413_051));
+        assert!(!miller_rabin(561));
+        assert!(miller_rabin(u64::MAX - 58));
+        assert!(!miller_rabin(u64::MAX));
+    }
+
+    #[test]
+    fn baillie_psw_matches_miller_rabin() {
+        let sieve = Sieve::new(1_000_000);
+        for n in 0..=1_000_000 {
+            assert_eq!(baillie_psw(n), sieve.lookup(n).unwrap(), "{}", n);
+        }
+        for n in (u64::MAX - 10_000..=u64::MAX).chain((1 << 32) - 10_000..(1 << 32) + 10_000) {
+            assert_eq!(baillie_psw(n), miller_rabin(n), "{}", n);
+        }
+        // Squares of primes, which have no `D` for the Lucas test
+        assert!(!baillie_psw(4_294_967_291 * 4_294_967_291));
+        assert!(!baillie_psw(1_000_003 * 1_000_003));
+    }
+
+    #[test]
+    fn baillie_psw_catches_pseudoprimes() {
+        // Strong pseudoprimes to base 2
+        for n in [2047, 3277, 4033, 3_215_031_751, 3_825_123_056_546_413_051] {
+            assert!(strong_fermat(n, 2), "{}", n);
+            assert!(!baillie_psw(n), "{}", n);
+        }
+        // Strong Lucas pseudoprimes
+        for n in [5459, 5777, 10877, 16109, 18971] {
+            assert!(strong_lucas(n), "{}", n);
+            assert!(!baillie_psw(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn lucas_lehmer_finds_mersenne_primes() {
+        let sieve = Sieve::new(1300);
+        let exponents: Vec<u32> = (0..=1300)
+            .filter(|&p| sieve.lucas_lehmer(p).unwrap())
+            .collect();
+        assert_eq!(
+            exponents,
+            vec![2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Python bindings, via PyO3.
+//!
+//! Build with the `python` feature as a `cdylib` named `prime_sieve` (e.g. with maturin, or
+//! `cargo rustc --lib --release --features python --crate-type cdylib` and renaming the output
+//! to `prime_sieve.so`), then `from prime_sieve import Sieve`.
+
+use pyo3::exceptions::{PyOverflowError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{Sieve, SieveError};
+
+impl From<SieveError> for PyErr {
+    fn from(error: SieveError) -> PyErr {
+        match error {
+            SieveError::NotFilled => PyRuntimeError::new_err(error.to_string()),
+            SieveError::OutOfBounds { .. }
+            | SieveError::BelowRange { .. }
+            | SieveError::Zero
+            | SieveError::NotPrime { .. }
+            | SieveError::InvalidPrimes => PyValueError::new_err(error.to_string()),
+            SieveError::Overflow => PyOverflowError::new_err(error.to_string()),
+        }
+    }
+}
+
+/// A filled prime sieve, exposed to Python as `prime_sieve.Sieve`.
+#[pyclass(name = "Sieve", module = "prime_sieve", frozen)]
+pub struct PySieve {
+    inner: Sieve,
+}
+
+#[pymethods]
+impl PySieve {
+    /// Create and populate a prime sieve with the maximum value `max`.
+    #[new]
+    fn new(max: u64) -> PySieve {
+        PySieve {
+            inner: Sieve::new(max),
+        }
+    }
+
+    /// The max value of this sieve.
+    #[getter]
+    fn max(&self) -> u64 {
+        self.inner.max()
+    }
+
+    /// Determine whether `target` is prime, raising `ValueError` if it's out of bounds.
+    fn lookup(&self, target: u64) -> PyResult<bool> {
+        Ok(self.inner.lookup(target)?)
+    }
+
+    /// Iterate over the primes in this sieve, in ascending order.
+    fn primes(slf: Py<PySieve>) -> PyPrimes {
+        PyPrimes {
+            sieve: slf,
+            next: 0,
+        }
+    }
+
+    /// Take any iterable of integers and return a list of just the primes, in order.
+    fn filter(&self, values: &Bound<'_, PyAny>) -> PyResult<Vec<u64>> {
+        let mut result = Vec::new();
+        for value in values.try_iter()? {
+            let value: u64 = value?.extract()?;
+            if self.inner.lookup(value)? {
+                result.push(value);
+            }
+        }
+        Ok(result)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Sieve({})", self.inner.max())
+    }
+}
+
+/// The iterator returned by `Sieve.primes()`.
+#[pyclass(name = "Primes", module = "prime_sieve")]
+pub struct PyPrimes {
+    sieve: Py<PySieve>,
+    next: u64,
+}
+
+#[pymethods]
+impl PyPrimes {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<u64> {
+        let prime = self.sieve.get().inner.primes_from(self.next).next()?;
+        self.next = prime + 1;
+        Some(prime)
+    }
+}
+
+#[pymodule]
+fn prime_sieve(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySieve>()?;
+    module.add_class::<PyPrimes>()?;
+    Ok(())
+}
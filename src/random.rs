@@ -0,0 +1,84 @@
+//! Picking primes at random.
+
+use core::ops::RangeBounds;
+
+use rand::{Rng, RngExt};
+
+use crate::{inclusive_bounds, Sieve};
+
+/// How many random numbers `random_prime` tries before falling back to counting the primes
+const GUESSES: usize = 128;
+
+impl Sieve {
+    /// Pick a prime within `range` uniformly at random, using `rng`. Any part of the range beyond
+    /// `sieve.max()` is ignored.
+    ///
+    /// Returns `None` if the sieve is unfilled or there are no primes in the range.
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    ///
+    /// let my_sieve = prime_sieve::Sieve::new(1_000_000);
+    /// let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+    ///
+    /// let p = my_sieve.random_prime(500_000.., &mut rng).unwrap();
+    /// assert!(p >= 500_000 && my_sieve.lookup(p).unwrap());
+    /// assert_eq!(my_sieve.random_prime(24..29, &mut rng), None);
+    /// ```
+    pub fn random_prime<R, G>(&self, range: R, rng: &mut G) -> Option<u64>
+    where
+        R: RangeBounds<u64>,
+        G: Rng + ?Sized,
+    {
+        let (start, end) = inclusive_bounds(&range);
+        let end = end.min(self.max);
+        if !self.filled || start > end {
+            return None;
+        }
+        // Guessing finds a prime after about `ln(end)` tries, and every prime is equally likely
+        // to be the one it finds. Ranges with few or no primes fall through to counting them,
+        // which is just as uniform.
+        for _ in 0..GUESSES {
+            let guess = rng.random_range(start..=end);
+            if self.is_prime_unchecked(guess) {
+                return Some(guess);
+            }
+        }
+        let count = self.primes_between(start, end).count();
+        if count == 0 {
+            return None;
+        }
+        self.primes_between(start, end)
+            .nth(rng.random_range(0..count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn picks_every_prime_in_range() {
+        let sieve = Sieve::new(1000);
+        let mut rng = SmallRng::seed_from_u64(7);
+        let mut seen = [0; 5];
+        for _ in 0..5000 {
+            let p = sieve.random_prime(100..=120, &mut rng).unwrap();
+            let i = [101, 103, 107, 109, 113]
+                .iter()
+                .position(|&q| q == p)
+                .unwrap();
+            seen[i] += 1;
+        }
+        assert!(seen.iter().all(|&n| n > 850 && n < 1150), "{:?}", seen);
+
+        assert_eq!(
+            sieve.random_prime(.., &mut rng).map(|p| p <= 1000),
+            Some(true)
+        );
+        assert_eq!(sieve.random_prime(2000.., &mut rng), None);
+        assert_eq!(Sieve::unfilled(10).random_prime(.., &mut rng), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Sieving a window of numbers far from zero, without sieving everything below it.
+
+use core::convert::TryFrom;
+
+use crate::bits::BitTable;
+use crate::{sqrt_floor, Sieve, SieveError};
+
+/// A filled sieve of just the numbers from `low()` to `high()` (inclusive), made with
+/// `Sieve::range()`.
+///
+/// The table takes a bit for every number in the window, plus the base primes up to `√high` while
+/// it's being made, so a window of `10^8` numbers takes about 12MB however far from zero it is.
+///
+/// ```
+/// let window = prime_sieve::Sieve::range(1_000_000_000_000, 1_000_000_001_000);
+///
+/// assert_eq!(window.count_primes(), 37);
+/// assert_eq!(window.primes().next(), Some(1_000_000_000_039));
+/// assert_eq!(window.lookup(1_000_000_000_039), Ok(true));
+/// ```
+#[derive(Debug)]
+pub struct RangeSieve {
+    low: u64,
+    high: u64,
+    // Bit `i` is set if `low + i` is prime
+    table: BitTable,
+}
+
+impl RangeSieve {
+    /// The smallest number in the window.
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+
+    /// The largest number in the window.
+    pub fn high(&self) -> u64 {
+        self.high
+    }
+
+    /// Determine whether a number within the window is prime or not.
+    ///
+    /// Returns `Err(SieveError::BelowRange)` if `target` is below the window's bottom, or
+    /// `Err(SieveError::OutOfBounds)` if it's above the top.
+    pub fn lookup(&self, target: u64) -> Result<bool, SieveError> {
+        if target < self.low {
+            return Err(SieveError::BelowRange {
+                value: target,
+                min: self.low,
+            });
+        }
+        if target > self.high {
+            return Err(SieveError::OutOfBounds {
+                value: target,
+                max: self.high,
+            });
+        }
+        Ok(self.table.get((target - self.low) as usize))
+    }
+
+    /// Iterate over every prime in the window, in ascending order.
+    pub fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut next = 0;
+        core::iter::from_fn(move || {
+            let i = self.table.next_set(next)?;
+            next = i + 1;
+            Some(self.low + i as u64)
+        })
+    }
+
+    /// Count the primes in the window.
+    pub fn count_primes(&self) -> u64 {
+        self.table
+            .words()
+            .iter()
+            .map(|word| u64::from(word.count_ones()))
+            .sum()
+    }
+}
+
+impl Sieve {
+    /// Sieve just the numbers from `low` to `high` (inclusive), using the primes up to `√high`.
+    ///
+    /// This is much quicker than a sieve with a max of `high` when the window is small compared to
+    /// how far it is from zero. An empty window (`low > high`) is fine and contains no primes.
+    ///
+    /// Panics if the window is too large to address on this platform.
+    pub fn range(low: u64, high: u64) -> RangeSieve {
+        let len = if low > high {
+            0
+        } else {
+            usize::try_from(high - low)
+                .ok()
+                .and_then(|len| len.checked_add(1))
+                .expect("Range is too large to address on this platform")
+        };
+        let mut table = BitTable::ones(len);
+        if len > 0 {
+            for p in Sieve::new(sqrt_floor(high)).primes() {
+                // Work in offsets from `low` so nothing overflows right at the top of the `u64`
+                // range, and leave `p` itself alone
+                let square = p * p;
+                let mut i = if square >= low {
+                    square - low
+                } else {
+                    (p - low % p) % p
+                } as usize;
+                while i < len {
+                    table.clear(i);
+                    i += p as usize;
+                }
+            }
+            for n in low..=high.min(1) {
+                table.clear((n - low) as usize);
+            }
+        }
+        RangeSieve { low, high, table }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primality::baillie_psw;
+
+    #[test]
+    fn matches_full_sieve() {
+        let sieve = Sieve::new(100_000);
+        for (low, high) in [
+            (0, 100_000),
+            (0, 0),
+            (1, 2),
+            (2, 2),
+            (97, 97),
+            (1000, 2000),
+            (5, 4),
+        ] {
+            let window = Sieve::range(low, high);
+            assert!(window.primes().eq(sieve.primes_in_range(low..=high)));
+            assert_eq!(window.count_primes(), window.primes().count() as u64);
+            for n in low.saturating_sub(3)..=high + 3 {
+                let expected = if n < low {
+                    Err(SieveError::BelowRange { value: n, min: low })
+                } else if n > high {
+                    Err(SieveError::OutOfBounds {
+                        value: n,
+                        max: high,
+                    })
+                } else {
+                    sieve.lookup(n)
+                };
+                assert_eq!(window.lookup(n), expected, "n {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn sieves_far_from_zero() {
+        for low in [100_000_000_000_000, 10_000_000_000_000_000] {
+            let high = low + 100_000;
+            let window = Sieve::range(low, high);
+            let expected: Vec<u64> = (low..=high).filter(|&n| baillie_psw(n)).collect();
+            assert_eq!(window.primes().collect::<Vec<u64>>(), expected);
+            assert_eq!(window.count_primes(), expected.len() as u64);
+        }
+    }
+}
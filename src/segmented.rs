@@ -0,0 +1,208 @@
+//! Block-by-block sieving, for sieves too large to keep a full table in memory.
+
+use alloc::vec::Vec;
+
+/// Determine whether `target` is prime by trial division.
+///
+/// `base_primes` must contain every prime up to `√target`, in ascending order.
+pub(crate) fn is_prime(base_primes: &[u64], target: u64) -> bool {
+    target >= 2
+        && base_primes
+            .iter()
+            .take_while(|&&p| p * p <= target)
+            .all(|&p| !target.is_multiple_of(p))
+}
+
+/// Sieve the numbers `low..low + segment.len()`, recording whether each one is prime in `segment`.
+///
+/// `base_primes` must contain every prime up to the square root of the top of the segment, in
+/// ascending order.
+pub(crate) fn sieve_segment(base_primes: &[u64], low: u64, segment: &mut [bool]) {
+    if segment.is_empty() {
+        return;
+    }
+    for flag in segment.iter_mut() {
+        *flag = true;
+    }
+    let high = low + (segment.len() as u64 - 1);
+    for &p in base_primes {
+        let square = p * p;
+        if square > high {
+            break;
+        }
+        // Work in offsets from `low` so nothing overflows right at the top of the `u64` range
+        let mut i = if square >= low {
+            square - low
+        } else {
+            (p - low % p) % p
+        } as usize;
+        while i < segment.len() {
+            segment[i] = false;
+            i += p as usize;
+        }
+    }
+    if low < 2 {
+        for flag in segment.iter_mut().take((2 - low) as usize) {
+            *flag = false;
+        }
+    }
+}
+
+/// Lazily yields the primes of a segmented sieve, one segment at a time, from either end.
+#[derive(Debug)]
+pub(crate) struct SegmentedPrimes<'a> {
+    base_primes: &'a [u64],
+    segment_size: u64,
+    // Every prime in `front..=back` is still to be yielded, unless `done`
+    front: u64,
+    back: u64,
+    done: bool,
+    front_segment: Segment,
+    back_segment: Segment,
+}
+
+/// A sieved run of the numbers `low..low + flags.len()`
+#[derive(Debug, Default)]
+struct Segment {
+    low: u64,
+    flags: Vec<bool>,
+}
+
+impl Segment {
+    fn contains(&self, n: u64) -> bool {
+        n >= self.low && n - self.low < self.flags.len() as u64
+    }
+
+    fn sieve(&mut self, base_primes: &[u64], low: u64, high: u64) {
+        self.flags.resize((high - low + 1) as usize, true);
+        sieve_segment(base_primes, low, &mut self.flags);
+        self.low = low;
+    }
+}
+
+impl<'a> SegmentedPrimes<'a> {
+    pub(crate) fn new(base_primes: &'a [u64], max: u64, segment_size: u64, from: u64) -> Self {
+        SegmentedPrimes {
+            base_primes,
+            segment_size,
+            front: from,
+            back: max,
+            done: from > max,
+            front_segment: Segment::default(),
+            back_segment: Segment::default(),
+        }
+    }
+
+    // Mark everything up to and including `n` as yielded
+    fn consume_front(&mut self, n: u64) {
+        if n >= self.back {
+            self.done = true;
+        } else {
+            self.front = n + 1;
+        }
+    }
+
+    // Mark everything from `n` upwards as yielded
+    fn consume_back(&mut self, n: u64) {
+        if n <= self.front {
+            self.done = true;
+        } else {
+            self.back = n - 1;
+        }
+    }
+}
+
+impl Iterator for SegmentedPrimes<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while !self.done {
+            if !self.front_segment.contains(self.front) {
+                let high = self
+                    .front
+                    .saturating_add(self.segment_size - 1)
+                    .min(self.back);
+                self.front_segment.sieve(self.base_primes, self.front, high);
+            }
+            let segment = &self.front_segment;
+            let start = (self.front - segment.low) as usize;
+            let end = (self.back - segment.low).min(segment.flags.len() as u64 - 1) as usize + 1;
+            match segment.flags[start..end].iter().position(|&flag| flag) {
+                Some(i) => {
+                    let prime = segment.low + (start + i) as u64;
+                    self.consume_front(prime);
+                    return Some(prime);
+                }
+                None => self.consume_front(segment.low + (end - 1) as u64),
+            }
+        }
+        None
+    }
+}
+
+impl DoubleEndedIterator for SegmentedPrimes<'_> {
+    fn next_back(&mut self) -> Option<u64> {
+        while !self.done {
+            if !self.back_segment.contains(self.back) {
+                let low = self
+                    .back
+                    .saturating_sub(self.segment_size - 1)
+                    .max(self.front);
+                self.back_segment.sieve(self.base_primes, low, self.back);
+            }
+            let segment = &self.back_segment;
+            let start = self.front.saturating_sub(segment.low) as usize;
+            let end = (self.back - segment.low) as usize + 1;
+            match segment.flags[start..end].iter().rposition(|&flag| flag) {
+                Some(i) => {
+                    let prime = segment.low + (start + i) as u64;
+                    self.consume_back(prime);
+                    return Some(prime);
+                }
+                None => self.consume_back(segment.low + start as u64),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_matches_trial_division() {
+        let base_primes = vec![2, 3, 5, 7, 11, 13];
+        let mut segment = vec![false; 50];
+        sieve_segment(&base_primes, 100, &mut segment);
+        for (i, &flag) in segment.iter().enumerate() {
+            assert_eq!(flag, is_prime(&base_primes, 100 + i as u64));
+        }
+    }
+
+    #[test]
+    fn segments_at_top_of_range() {
+        let mut segment = vec![false; 10];
+        sieve_segment(&[2, 3], u64::MAX - 9, &mut segment);
+        assert!(!segment[9]);
+    }
+
+    #[test]
+    fn iterates_from_both_ends() {
+        let base_primes = [2, 3, 5, 7];
+        let forwards: Vec<u64> = SegmentedPrimes::new(&base_primes, 100, 7, 0).collect();
+        let mut backwards: Vec<u64> = SegmentedPrimes::new(&base_primes, 100, 7, 0)
+            .rev()
+            .collect();
+        backwards.reverse();
+        assert_eq!(forwards, backwards);
+        assert_eq!(forwards.len(), 25);
+
+        let mut primes = SegmentedPrimes::new(&base_primes, 100, 7, 10);
+        assert_eq!(primes.next_back(), Some(97));
+        assert_eq!(primes.next(), Some(11));
+        assert_eq!(primes.nth_back(18), Some(13));
+        assert_eq!(primes.next(), None);
+        assert_eq!(primes.next_back(), None);
+    }
+}
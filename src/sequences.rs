@@ -0,0 +1,252 @@
+//! Queries about particular patterns and families of primes.
+
+use crate::{Sieve, SieveError};
+
+impl Sieve {
+    /// Iterate over every pair of twin primes `(p, p + 2)` with `p + 2 <= sieve.max()`, in
+    /// ascending order.
+    ///
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(50);
+    ///
+    /// let twins: Vec<(u64, u64)> = my_sieve.twin_primes().collect();
+    /// assert_eq!(twins, vec![(3, 5), (5, 7), (11, 13), (17, 19), (29, 31), (41, 43)]);
+    /// ```
+    pub fn twin_primes(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.primes()
+            .zip(self.primes().skip(1))
+            .filter(|&(p, q)| q - p == 2)
+    }
+
+    /// Iterate over every prime alongside the gap to the next prime, in ascending order.
+    ///
+    /// The largest prime in the sieve is left out, since the next prime is beyond its bounds.
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(15);
+    ///
+    /// let gaps: Vec<(u64, u64)> = my_sieve.gaps().collect();
+    /// assert_eq!(gaps, vec![(2, 1), (3, 2), (5, 2), (7, 4), (11, 2)]);
+    /// ```
+    pub fn gaps(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.primes()
+            .zip(self.primes().skip(1))
+            .map(|(p, q)| (p, q - p))
+    }
+
+    /// Iterate over every occurrence of a prime constellation, i.e. each `n` for which `n + offset`
+    /// is prime for every `offset` in `pattern`, in ascending order. Only occurrences that fit
+    /// entirely within the sieve are found.
+    ///
+    /// Patterns usually start at `0`, making `n` the first prime of each occurrence - e.g.
+    /// `[0, 2, 6, 8]` finds prime quadruplets. Yields nothing if the sieve is unfilled or
+    /// `pattern` is empty.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    ///
+    /// let quadruplets: Vec<u64> = my_sieve.k_tuples(&[0, 2, 6, 8]).collect();
+    /// assert_eq!(quadruplets, vec![5, 11, 101, 191, 821]);
+    /// ```
+    pub fn k_tuples<'a>(&'a self, pattern: &'a [u64]) -> impl Iterator<Item = u64> + 'a {
+        let lowest = pattern.iter().copied().min().unwrap_or(0);
+        let highest = pattern.iter().copied().max().unwrap_or(0);
+        // Walk the primes at the lowest offset, stopping where the highest would leave the sieve
+        let primes = match self.max.checked_sub(highest - lowest) {
+            Some(end) if !pattern.is_empty() => self.primes_between(lowest, end),
+            _ => self.primes_between(1, 0),
+        };
+        primes.map(move |p| p - lowest).filter(move |&n| {
+            pattern
+                .iter()
+                .all(|&offset| self.is_prime_unchecked(n + offset))
+        })
+    }
+
+    /// Find the largest gap between consecutive primes in the sieve, as `(prime, gap)` where
+    /// `prime` is the start of the gap. Ties go to the first occurrence.
+    ///
+    /// Returns `None` if the sieve is unfilled or has fewer than two primes.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    /// assert_eq!(my_sieve.max_gap(), Some((887, 20)));
+    /// ```
+    pub fn max_gap(&self) -> Option<(u64, u64)> {
+        self.gaps()
+            .fold(None, |best: Option<(u64, u64)>, (p, gap)| match best {
+                Some((_, best_gap)) if best_gap >= gap => best,
+                _ => Some((p, gap)),
+            })
+    }
+
+    /// Determine whether `p` is a Sophie Germain prime, i.e. both `p` and `2p + 1` are prime.
+    ///
+    /// Returns the same errors as `Sieve::lookup()`, including `Err(SieveError::OutOfBounds)` if
+    /// `p` is prime but `2p + 1 > sieve.max()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_sophie_germain(11), Ok(true));
+    /// assert_eq!(my_sieve.is_sophie_germain(13), Ok(false));
+    /// assert!(my_sieve.is_sophie_germain(53).is_err());
+    /// ```
+    pub fn is_sophie_germain(&self, p: u64) -> Result<bool, SieveError> {
+        if !self.lookup(p)? {
+            return Ok(false);
+        }
+        match p.checked_mul(2).and_then(|double| double.checked_add(1)) {
+            Some(safe) => self.lookup(safe),
+            // Too big for a `u64`, so certainly too big for the sieve
+            None => Err(SieveError::OutOfBounds {
+                value: u64::MAX,
+                max: self.max,
+            }),
+        }
+    }
+
+    /// Determine whether `p` is a safe prime, i.e. both `p` and `(p - 1) / 2` are prime.
+    ///
+    /// Returns the same errors as `Sieve::lookup()`.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.is_safe_prime(23), Ok(true));
+    /// assert_eq!(my_sieve.is_safe_prime(29), Ok(false));
+    /// ```
+    pub fn is_safe_prime(&self, p: u64) -> Result<bool, SieveError> {
+        Ok(self.lookup(p)? && p >= 5 && self.is_prime_unchecked((p - 1) / 2))
+    }
+
+    /// Iterate over every Sophie Germain prime `p` with `2p + 1 <= sieve.max()`, in ascending
+    /// order.
+    ///
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let primes: Vec<u64> = my_sieve.sophie_germain_primes().collect();
+    /// assert_eq!(primes, vec![2, 3, 5, 11, 23, 29, 41]);
+    /// ```
+    pub fn sophie_germain_primes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.primes_in_range(..=(self.max - self.max.min(1)) / 2)
+            .filter(move |&p| self.is_prime_unchecked(2 * p + 1))
+    }
+
+    /// Iterate over every safe prime in the sieve, in ascending order.
+    ///
+    /// Yields nothing if the sieve is unfilled.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(100);
+    ///
+    /// let primes: Vec<u64> = my_sieve.safe_primes().collect();
+    /// assert_eq!(primes, vec![5, 7, 11, 23, 47, 59, 83]);
+    /// ```
+    pub fn safe_primes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.sophie_germain_primes().map(|p| 2 * p + 1)
+    }
+
+    /// Find two primes `(p, q)` with `p <= q` and `p + q == even_n`, choosing the smallest `p`.
+    ///
+    /// Returns `None` if the sieve is unfilled, `even_n` is odd, less than 4 or greater than
+    /// `sieve.max()`, or (as would disprove Goldbach's conjecture) there's no such pair.
+    ///
+    /// ```
+    /// let my_sieve = prime_sieve::Sieve::new(1000);
+    ///
+    /// assert_eq!(my_sieve.goldbach(100), Some((3, 97)));
+    /// assert_eq!(my_sieve.goldbach(99), None);
+    /// ```
+    pub fn goldbach(&self, even_n: u64) -> Option<(u64, u64)> {
+        if even_n < 4 || !even_n.is_multiple_of(2) || even_n > self.max {
+            return None;
+        }
+        self.primes()
+            .take_while(|&p| p <= even_n / 2)
+            .find(|&p| self.is_prime_unchecked(even_n - p))
+            .map(|p| (p, even_n - p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_twin_primes() {
+        // There are 8169 twin prime pairs below 10^6
+        assert_eq!(Sieve::new(1_000_000).twin_primes().count(), 8169);
+        assert_eq!(Sieve::new(4).twin_primes().count(), 0);
+        assert_eq!(Sieve::new(5).twin_primes().count(), 1);
+    }
+
+    #[test]
+    fn finds_constellations() {
+        let sieve = Sieve::new(1_000_000);
+        // There are 166 prime quadruplets below 10^6
+        assert_eq!(sieve.k_tuples(&[0, 2, 6, 8]).count(), 166);
+        let twins: Vec<u64> = sieve.k_tuples(&[0, 2]).collect();
+        let expected: Vec<u64> = sieve.twin_primes().map(|(p, _)| p).collect();
+        assert_eq!(twins, expected);
+
+        // Patterns are taken as given, in any order and without needing a 0
+        let small = Sieve::new(20);
+        assert_eq!(
+            small.k_tuples(&[4, 2]).collect::<Vec<u64>>(),
+            vec![1, 3, 9, 15]
+        );
+        assert_eq!(small.k_tuples(&[0, 1]).collect::<Vec<u64>>(), vec![2]);
+        assert_eq!(small.k_tuples(&[0, 100]).count(), 0);
+        assert_eq!(small.k_tuples(&[]).count(), 0);
+        assert_eq!(Sieve::unfilled(20).k_tuples(&[0, 2]).count(), 0);
+    }
+
+    #[test]
+    fn goldbach_holds() {
+        let sieve = Sieve::new(10_000);
+        assert_eq!(sieve.goldbach(4), Some((2, 2)));
+        for n in (4..=10_000).step_by(2) {
+            let (p, q) = sieve.goldbach(n).unwrap();
+            assert_eq!(p + q, n);
+            assert!(sieve.lookup(p).unwrap() && sieve.lookup(q).unwrap());
+        }
+        assert_eq!(sieve.goldbach(10_002), None);
+        assert_eq!(Sieve::unfilled(10).goldbach(4), None);
+    }
+
+    #[test]
+    fn sophie_germain_pairs_with_safe() {
+        let sieve = Sieve::new(100_000);
+        let germain: Vec<u64> = sieve.sophie_germain_primes().collect();
+        let safe: Vec<u64> = sieve.safe_primes().collect();
+        // There are 670 Sophie Germain primes below 50,000
+        assert_eq!(germain.len(), 670);
+        assert_eq!(safe.len(), 670);
+        for (&p, &q) in germain.iter().zip(&safe) {
+            assert_eq!(sieve.is_sophie_germain(p), Ok(true));
+            assert_eq!(sieve.is_safe_prime(q), Ok(true));
+            assert_eq!(q, 2 * p + 1);
+        }
+        assert_eq!(sieve.is_safe_prime(2), Ok(false));
+        assert_eq!(sieve.is_safe_prime(3), Ok(false));
+        assert_eq!(Sieve::new(0).sophie_germain_primes().count(), 0);
+        assert_eq!(Sieve::new(4).sophie_germain_primes().count(), 0);
+        assert_eq!(Sieve::new(5).sophie_germain_primes().count(), 1);
+    }
+
+    #[test]
+    fn gaps_sum_to_range() {
+        let sieve = Sieve::new(100_000);
+        let total: u64 = sieve.gaps().map(|(_, gap)| gap).sum();
+        assert_eq!(total, sieve.prev_prime(100_001).unwrap() - 2);
+        assert_eq!(sieve.max_gap(), Some((31_397, 72)));
+        assert_eq!(Sieve::new(2).max_gap(), None);
+    }
+}
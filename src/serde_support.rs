@@ -0,0 +1,114 @@
+//! Validation for deserialized sieves, so corrupt input can't produce an inconsistent table.
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use serde::{Deserialize, Serializer};
+
+use crate::bits::BitTable;
+use crate::{wheel, Sieve, Table};
+
+// Tables are always serialized as a plain list of words, however they're stored
+pub(crate) fn serialize_words<S: Serializer>(
+    words: &[u64],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(words)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RawBitTable {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl TryFrom<RawBitTable> for BitTable {
+    type Error = &'static str;
+
+    fn try_from(raw: RawBitTable) -> Result<BitTable, Self::Error> {
+        BitTable::from_words(raw.words, raw.len).ok_or("bit table has the wrong number of words")
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RawSieve {
+    max: u64,
+    table: Table,
+    filled: bool,
+}
+
+impl TryFrom<RawSieve> for Sieve {
+    type Error = &'static str;
+
+    fn try_from(raw: RawSieve) -> Result<Sieve, Self::Error> {
+        match raw.table {
+            Table::Full(table) => {
+                if table.len() as u64 != wheel::count(raw.max) {
+                    return Err("sieve table doesn't match the max value");
+                }
+                Ok(Sieve {
+                    max: raw.max,
+                    table: Table::Full(table),
+                    filled: raw.filled,
+                    fill_state: None,
+                })
+            }
+            Table::Segmented { segment_size, .. } => {
+                if segment_size == 0 {
+                    return Err("segment size must be non-zero");
+                }
+                // The base primes are cheap to find again, and trusting them would let a
+                // truncated list make every lookup above its end wrong, so they're recomputed
+                let mut sieve = Sieve {
+                    max: raw.max,
+                    table: Table::Segmented {
+                        segment_size,
+                        base_primes: Vec::new(),
+                    },
+                    filled: false,
+                    fill_state: None,
+                };
+                if raw.filled {
+                    sieve.fill();
+                }
+                Ok(sieve)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        for original in [
+            Sieve::new(1000),
+            Sieve::unfilled(50),
+            Sieve::segmented(1000, 10),
+        ] {
+            let json = serde_json::to_string(&original).unwrap();
+            let restored: Sieve = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.max(), original.max());
+            assert_eq!(restored.first_difference(&original), None);
+        }
+    }
+
+    #[test]
+    fn recomputes_base_primes() {
+        let original = Sieve::segmented(1_000_000, 1000);
+        let mut json: serde_json::Value = serde_json::to_value(&original).unwrap();
+        let base_primes = &mut json["table"]["Segmented"]["base_primes"];
+        base_primes.as_array_mut().unwrap().truncate(3);
+        let restored: Sieve = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.first_difference(&original), None);
+        assert_eq!(restored.lookup(999_983), Ok(true));
+    }
+
+    #[test]
+    fn rejects_mismatched_table() {
+        let mut json: serde_json::Value = serde_json::to_value(Sieve::new(1000)).unwrap();
+        json["max"] = serde_json::Value::from(2000);
+        assert!(serde_json::from_value::<Sieve>(json).is_err());
+    }
+}
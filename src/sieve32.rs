@@ -0,0 +1,104 @@
+//! A `u32` front-end for sieves that never need to go beyond `u32::MAX`.
+
+use alloc::vec::Vec;
+
+use crate::{Sieve, SieveError};
+
+/// A prime sieve whose values are all `u32`s.
+///
+/// The table is the same as `Sieve`'s, but inputs and outputs don't need converting to and from
+/// `u64` and collected results (e.g. from `filter`) take half the memory.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve32::new(100);
+///
+/// let primes: Vec<u32> = my_sieve.primes().take(4).collect();
+/// assert_eq!(primes, vec![2, 3, 5, 7]);
+/// ```
+#[derive(Debug)]
+pub struct Sieve32 {
+    inner: Sieve,
+}
+
+impl Sieve32 {
+    /// Create a new prime sieve with the maximum value `max`, but *do not* populate it.
+    pub fn unfilled(max: u32) -> Sieve32 {
+        Sieve32 {
+            inner: Sieve::unfilled(max.into()),
+        }
+    }
+
+    /// Create and populate a prime sieve with the maximum value `max`.
+    pub fn new(max: u32) -> Sieve32 {
+        Sieve32 {
+            inner: Sieve::new(max.into()),
+        }
+    }
+
+    /// Populate an unfilled sieve. Has no effect on already-filled sieves.
+    pub fn fill(&mut self) {
+        self.inner.fill();
+    }
+
+    /// Get the max value of this sieve
+    pub fn max(&self) -> u32 {
+        self.inner.max() as u32
+    }
+
+    /// Determine whether a number within the prime sieve's limits is prime or not.
+    ///
+    /// Returns the same errors as `Sieve::lookup()`.
+    pub fn lookup(&self, target: u32) -> Result<bool, SieveError> {
+        self.inner.lookup(target.into())
+    }
+
+    /// Iterate over every prime in this sieve, in ascending order.
+    pub fn primes(&self) -> impl Iterator<Item = u32> + '_ {
+        // Every prime is at most `max`, which is a `u32`
+        self.inner.primes().map(|p| p as u32)
+    }
+
+    /// Takes any collection or iterator of `u32`s and returns just the prime ones, in order.
+    ///
+    /// Returns the same errors as `Sieve::filter()`.
+    pub fn filter<I: IntoIterator<Item = u32>>(&self, target: I) -> Result<Vec<u32>, SieveError> {
+        let mut result = Vec::new();
+        for i in target.into_iter() {
+            if self.lookup(i)? {
+                result.push(i);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Borrow the underlying `u64` sieve, for everything not covered by the `u32` API.
+    pub fn as_sieve(&self) -> &Sieve {
+        &self.inner
+    }
+}
+
+impl From<Sieve32> for Sieve {
+    fn from(sieve: Sieve32) -> Sieve {
+        sieve.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_u64_sieve() {
+        let small = Sieve32::new(10_000);
+        let large = Sieve::new(10_000);
+        assert!(small.primes().map(u64::from).eq(large.primes()));
+        assert_eq!(small.filter(vec![1, 2, 3, 4]).unwrap(), vec![2, 3]);
+        assert_eq!(
+            small.lookup(10_001),
+            Err(SieveError::OutOfBounds {
+                value: 10_001,
+                max: 10_000
+            })
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Borrowed views of part of a sieve.
+
+use core::ops::{Bound, RangeBounds};
+
+use crate::{inclusive_bounds, Primes, Sieve, SieveError};
+
+/// A view of the numbers from `start()` to `end()` (inclusive) in a filled sieve, made with
+/// `Sieve::slice()`.
+///
+/// This borrows the sieve's table rather than copying any of it, so it's cheap to make and hand
+/// around.
+///
+/// ```
+/// let my_sieve = prime_sieve::Sieve::new(2_000_000);
+/// let slice = my_sieve.slice(1_000_000..=2_000_000).unwrap();
+///
+/// assert_eq!(slice.count(), 70_435);
+/// assert_eq!(slice.primes().next(), Some(1_000_003));
+/// assert_eq!(slice.lookup(1_999_993), Ok(true));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SieveSlice<'a> {
+    sieve: &'a Sieve,
+    start: u64,
+    end: u64,
+}
+
+impl<'a> SieveSlice<'a> {
+    /// The smallest number in the slice.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The largest number in the slice.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Determine whether a number within the slice is prime or not.
+    ///
+    /// Returns `Err(SieveError::BelowRange)` if `target` is below the slice's start, or
+    /// `Err(SieveError::OutOfBounds)` if it's above the end.
+    pub fn lookup(&self, target: u64) -> Result<bool, SieveError> {
+        if target < self.start {
+            return Err(SieveError::BelowRange {
+                value: target,
+                min: self.start,
+            });
+        }
+        if target > self.end {
+            return Err(SieveError::OutOfBounds {
+                value: target,
+                max: self.end,
+            });
+        }
+        self.sieve.lookup(target)
+    }
+
+    /// Iterate over every prime in the slice, in ascending order.
+    pub fn primes(&self) -> Primes<'a> {
+        self.sieve.primes_between(self.start, self.end)
+    }
+
+    /// Count the primes in the slice.
+    pub fn count(&self) -> u64 {
+        self.primes().count() as u64
+    }
+}
+
+impl Sieve {
+    /// Borrow the numbers in `range` as a `SieveSlice`. A range with no end stops at
+    /// `sieve.max()`.
+    ///
+    /// Returns `Err(SieveError::NotFilled)` if sieve is unpopulated, or
+    /// `Err(SieveError::OutOfBounds)` if the range ends past `sieve.max()`.
+    ///
+    /// ```
+    /// use prime_sieve::{Sieve, SieveError};
+    ///
+    /// let my_sieve = Sieve::new(100);
+    ///
+    /// assert_eq!(my_sieve.slice(90..).unwrap().count(), 1);
+    /// assert_eq!(
+    ///     my_sieve.slice(..=101).unwrap_err(),
+    ///     SieveError::OutOfBounds { value: 101, max: 100 }
+    /// );
+    /// ```
+    pub fn slice<R: RangeBounds<u64>>(&self, range: R) -> Result<SieveSlice<'_>, SieveError> {
+        let (start, end) = inclusive_bounds(&range);
+        let end = match range.end_bound() {
+            Bound::Unbounded => self.max,
+            _ => end,
+        };
+        if !self.filled {
+            return Err(SieveError::NotFilled);
+        }
+        if end > self.max {
+            return Err(SieveError::OutOfBounds {
+                value: end,
+                max: self.max,
+            });
+        }
+        Ok(SieveSlice {
+            sieve: self,
+            start,
+            end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_match_sieve() {
+        let sieve = Sieve::new(10_000);
+        let segmented = Sieve::segmented(10_000, 100);
+        for (start, end) in [(0, 10_000), (1000, 2000), (97, 97), (98, 100), (500, 400)] {
+            let slice = sieve.slice(start..=end).unwrap();
+            let expected: Vec<u64> = sieve.primes().filter(|&p| p >= start && p <= end).collect();
+            assert_eq!(slice.primes().collect::<Vec<u64>>(), expected);
+            assert_eq!(slice.count(), expected.len() as u64);
+            let segmented_slice = segmented.slice(start..=end).unwrap();
+            assert!(slice.primes().eq(segmented_slice.primes()));
+
+            for n in start.saturating_sub(5)..=end + 5 {
+                let expected = if n < start {
+                    Err(SieveError::BelowRange {
+                        value: n,
+                        min: start,
+                    })
+                } else if n > end {
+                    Err(SieveError::OutOfBounds { value: n, max: end })
+                } else {
+                    sieve.lookup(n)
+                };
+                assert_eq!(slice.lookup(n), expected, "n {}", n);
+            }
+        }
+
+        assert_eq!(sieve.slice(..).unwrap().end(), 10_000);
+        assert_eq!(sieve.slice(..10).unwrap().end(), 9);
+        assert_eq!(sieve.slice(5..).unwrap().start(), 5);
+        assert_eq!(
+            Sieve::unfilled(10).slice(..).unwrap_err(),
+            SieveError::NotFilled
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! An endless source of primes, for when there's no sensible upper bound to pick up front.
+
+use crate::Sieve;
+
+/// The max of the sieve a new stream starts out with
+const INITIAL_MAX: u64 = 1 << 10;
+
+/// Yields every prime in ascending order, doubling the max of its internal sieve whenever it runs
+/// out.
+///
+/// ```
+/// use prime_sieve::PrimeStream;
+///
+/// let first: Vec<u64> = PrimeStream::new().take(10).collect();
+/// assert_eq!(first, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+///
+/// assert_eq!(PrimeStream::new().nth(9_999), Some(104_729));
+/// ```
+#[derive(Debug)]
+pub struct PrimeStream {
+    sieve: Sieve,
+    next: u64,
+}
+
+impl PrimeStream {
+    /// Create a stream starting from the first prime, `2`.
+    pub fn new() -> PrimeStream {
+        PrimeStream {
+            sieve: Sieve::new(INITIAL_MAX),
+            next: 0,
+        }
+    }
+
+    /// The sieve backing this stream, which covers at least every prime yielded so far.
+    ///
+    /// ```
+    /// let mut stream = prime_sieve::PrimeStream::new();
+    /// stream.find(|&p| p > 5000);
+    ///
+    /// assert!(stream.sieve().max() > 5000);
+    /// ```
+    pub fn sieve(&self) -> &Sieve {
+        &self.sieve
+    }
+}
+
+impl Default for PrimeStream {
+    fn default() -> PrimeStream {
+        PrimeStream::new()
+    }
+}
+
+impl Iterator for PrimeStream {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            if let Some(p) = self.sieve.primes_from(self.next).next() {
+                self.next = p + 1;
+                return Some(p);
+            }
+            let max = self.sieve.max();
+            if max == u64::MAX {
+                return None;
+            }
+            self.sieve.extend_to(max.saturating_mul(2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_fixed_sieve_across_growth() {
+        let expected: Vec<u64> = Sieve::new(20_000).primes().collect();
+        let streamed: Vec<u64> = PrimeStream::new().take(expected.len()).collect();
+        assert_eq!(streamed, expected);
+    }
+}
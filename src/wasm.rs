@@ -0,0 +1,66 @@
+//! JavaScript bindings, via wasm-bindgen.
+//!
+//! Build with the `wasm` feature as a `cdylib`, e.g.
+//! `cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib`
+//! and then run `wasm-bindgen` over the output. Numbers cross the boundary as JS `BigInt`s.
+
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Sieve;
+
+/// A filled prime sieve, exposed to JS as `Sieve`.
+#[wasm_bindgen(js_name = Sieve)]
+pub struct WasmSieve {
+    inner: Rc<Sieve>,
+}
+
+#[wasm_bindgen(js_class = Sieve)]
+impl WasmSieve {
+    /// Create and populate a prime sieve with the maximum value `max`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(max: u64) -> WasmSieve {
+        WasmSieve {
+            inner: Rc::new(Sieve::new(max)),
+        }
+    }
+
+    /// Get the max value of this sieve.
+    pub fn max(&self) -> u64 {
+        self.inner.max()
+    }
+
+    /// Determine whether `target` is prime, throwing if it's out of this sieve's bounds.
+    pub fn lookup(&self, target: u64) -> Result<bool, JsError> {
+        Ok(self.inner.lookup(target)?)
+    }
+
+    /// Get an iterator over the primes in this sieve, in ascending order.
+    pub fn primes(&self) -> WasmPrimes {
+        WasmPrimes {
+            sieve: Rc::clone(&self.inner),
+            next: 0,
+        }
+    }
+}
+
+/// An iterator over the primes of a `Sieve`, exposed to JS as `Primes`.
+///
+/// `next()` returns `undefined` once every prime has been yielded.
+#[wasm_bindgen(js_name = Primes)]
+pub struct WasmPrimes {
+    sieve: Rc<Sieve>,
+    next: u64,
+}
+
+#[wasm_bindgen(js_class = Primes)]
+impl WasmPrimes {
+    /// Get the next prime, or `undefined` if there are none left.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<u64> {
+        let prime = self.sieve.primes_from(self.next).next()?;
+        self.next = prime + 1;
+        Some(prime)
+    }
+}
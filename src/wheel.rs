@@ -0,0 +1,57 @@
+//! Mapping between numbers and their positions on a mod-30 wheel.
+//!
+//! Only the 8 residues coprime to 30 can be prime (besides 2, 3 and 5 themselves), so the full
+//! table only stores those: candidate `i` is the number `30 * (i / 8) + RESIDUES[i % 8]`.
+
+/// The primes factored out by the wheel
+pub(crate) const WHEEL_PRIMES: [u64; 3] = [2, 3, 5];
+
+/// Residues mod 30 that are coprime to 30, in ascending order
+const RESIDUES: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// For every residue mod 30, how many entries of `RESIDUES` are strictly less than it
+const RESIDUES_BELOW: [u64; 30] = [
+    0, 0, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 4, 4, 4, 4, 5, 5, 6, 6, 6, 6, 7, 7, 7, 7, 7, 7,
+];
+
+/// The number of wheel candidates in `0..=max`
+pub(crate) fn count(max: u64) -> u64 {
+    (max / 30) * 8 + RESIDUES_BELOW[(max % 30) as usize] + u64::from(is_candidate(max))
+}
+
+/// Whether `n` is coprime to 30, i.e. has a position on the wheel
+pub(crate) fn is_candidate(n: u64) -> bool {
+    RESIDUES.contains(&(n % 30))
+}
+
+/// The position of the first wheel candidate `>= n`
+pub(crate) fn index_at_or_after(n: u64) -> u64 {
+    (n / 30) * 8 + RESIDUES_BELOW[(n % 30) as usize]
+}
+
+// Warning: doesn't check if `n` is coprime to 30
+pub(crate) fn index(n: u64) -> u64 {
+    index_at_or_after(n)
+}
+
+/// The number at position `i` on the wheel
+pub(crate) const fn value(i: u64) -> u64 {
+    30 * (i / 8) + RESIDUES[(i % 8) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_value_round_trip() {
+        let candidates: Vec<u64> = (0..1000).filter(|&n| is_candidate(n)).collect();
+        for (i, &n) in candidates.iter().enumerate() {
+            assert_eq!(index(n), i as u64);
+            assert_eq!(value(i as u64), n);
+        }
+        assert_eq!(count(999), candidates.len() as u64);
+        assert_eq!(count(0), 0);
+        assert_eq!(count(1), 1);
+    }
+}